@@ -0,0 +1,257 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Ident, Member};
+
+/// Implements `Behavior` for a struct that implements `Update`, storing the
+/// node status in the field marked `#[behave(status)]` and, optionally, its
+/// name in the `Option<String>` field marked `#[behave(name)]`.
+///
+/// ```
+/// use behave::*;
+///
+/// #[derive(Behavior)]
+/// struct Idle {
+///     #[behave(status)]
+///     status: Status,
+///     #[behave(name)]
+///     name: Option<String>,
+/// }
+///
+/// impl Update for Idle {
+///     fn update(&mut self, _: &mut Context) -> Status {
+///         Status::Running
+///     }
+/// }
+/// ```
+///
+/// A status field is required:
+///
+/// ```compile_fail
+/// use behave::*;
+///
+/// #[derive(Behavior)]
+/// struct Idle {
+///     status: Status,
+/// }
+///
+/// impl Update for Idle {
+///     fn update(&mut self, _: &mut Context) -> Status {
+///         Status::Running
+///     }
+/// }
+/// ```
+///
+/// Fields only take `status` or `name`:
+///
+/// ```compile_fail
+/// use behave::*;
+///
+/// #[derive(Behavior)]
+/// struct Idle {
+///     #[behave(status)]
+///     status: Status,
+///     #[behave(label)]
+///     label: Option<String>,
+/// }
+///
+/// impl Update for Idle {
+///     fn update(&mut self, _: &mut Context) -> Status {
+///         Status::Running
+///     }
+/// }
+/// ```
+///
+/// Each marker goes on a single field:
+///
+/// ```compile_fail
+/// use behave::*;
+///
+/// #[derive(Behavior)]
+/// struct Idle {
+///     #[behave(status)]
+///     status: Status,
+///     #[behave(status)]
+///     previous: Status,
+/// }
+///
+/// impl Update for Idle {
+///     fn update(&mut self, _: &mut Context) -> Status {
+///         Status::Running
+///     }
+/// }
+/// ```
+///
+/// Only structs are supported:
+///
+/// ```compile_fail
+/// use behave::*;
+///
+/// #[derive(Behavior)]
+/// enum Idle {
+///     Waiting,
+/// }
+///
+/// impl Update for Idle {
+///     fn update(&mut self, _: &mut Context) -> Status {
+///         Status::Running
+///     }
+/// }
+/// ```
+///
+/// The type itself only takes `persist` or `ports`:
+///
+/// ```compile_fail
+/// use behave::*;
+///
+/// #[derive(Behavior)]
+/// #[behave(shared)]
+/// struct Idle {
+///     #[behave(status)]
+///     status: Status,
+/// }
+///
+/// impl Update for Idle {
+///     fn update(&mut self, _: &mut Context) -> Status {
+///         Status::Running
+///     }
+/// }
+/// ```
+#[proc_macro_derive(Behavior, attributes(behave))]
+pub fn derive_behavior(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Behavior can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut persist = false;
+    let mut ports = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("behave"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("persist") {
+                persist = true;
+                Ok(())
+            } else if meta.path.is_ident("ports") {
+                ports = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `persist` or `ports`"))
+            }
+        })?;
+    }
+
+    let mut status = None;
+    let mut name = None;
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("behave"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let slot = if meta.path.is_ident("status") {
+                    &mut status
+                } else if meta.path.is_ident("name") {
+                    &mut name
+                } else {
+                    return Err(meta.error("expected `status` or `name`"));
+                };
+                if slot.is_some() {
+                    return Err(meta.error("field is already marked"));
+                }
+                *slot = Some(member.clone());
+                Ok(())
+            })?;
+        }
+    }
+    let status = status.ok_or_else(|| missing(&input.ident, "status"))?;
+
+    let naming = name.map(|name| {
+        quote! {
+            fn name(&self) -> &str {
+                self.#name.as_deref().unwrap_or_else(|| ::behave::Behavior::kind(self))
+            }
+
+            fn set_name(&mut self, name: &str) {
+                self.#name = Some(name.to_string());
+            }
+        }
+    });
+    let persistence = if persist {
+        Some(quote! {
+            fn persist(&self) -> Option<&dyn ::behave::persist::Persist> {
+                Some(self)
+            }
+
+            fn persist_mut(&mut self) -> Option<&mut dyn ::behave::persist::Persist> {
+                Some(self)
+            }
+        })
+    } else {
+        None
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // Types with ports write their own `CustomBehavior` impl to declare them.
+    let custom = if ports {
+        None
+    } else {
+        Some(quote! {
+            impl #impl_generics ::behave::CustomBehavior for #ident #ty_generics #where_clause {}
+        })
+    };
+    Ok(quote! {
+        impl #impl_generics ::behave::Behavior for #ident #ty_generics #where_clause {
+            fn initialize(&mut self, context: &mut ::behave::Context) {
+                self.#status = ::behave::Status::Running;
+                ::behave::Update::initialize(self, context);
+            }
+
+            fn update(&mut self, context: &mut ::behave::Context) -> ::behave::Status {
+                let status = ::behave::Update::update(self, context);
+                self.#status = status;
+                status
+            }
+
+            fn status(&self) -> ::behave::Status {
+                self.#status
+            }
+
+            fn on_complete(&mut self, result: ::behave::Status) {
+                self.#status = result;
+            }
+
+            #naming
+
+            #persistence
+        }
+
+        #custom
+    })
+}
+
+fn missing(ident: &Ident, attr: &str) -> Error {
+    Error::new_spanned(
+        ident,
+        format!("Behavior requires a field marked `#[behave({})]`", attr),
+    )
+}
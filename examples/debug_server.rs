@@ -0,0 +1,24 @@
+extern crate behave;
+
+use behave::debug::Server;
+use behave::helpers::*;
+use behave::*;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![
+            sequence(vec![named("listen", wait(2)), condition(|| false)]),
+            repeater(named("patrol", wait(1)), 3),
+        ]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:7878", tree).expect("bind 127.0.0.1:7878");
+    server.restart_on_finish(true);
+    println!("serving on 127.0.0.1:7878");
+    loop {
+        server.poll();
+        thread::sleep(Duration::from_millis(200));
+    }
+}
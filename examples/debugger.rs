@@ -0,0 +1,27 @@
+extern crate behave;
+
+use behave::debugger::Debugger;
+use behave::*;
+use std::io::{self, BufRead, Write};
+
+fn main() {
+    let mut debugger = Debugger::new(Tree::new(tree! {
+        selector {
+            sequence { cond(|| false), action(|| Status::Success) },
+            repeater(3) { action(|| Status::Success) }
+        }
+    }));
+    debugger.restart();
+    println!("{}", debugger.render());
+
+    let stdin = io::stdin();
+    loop {
+        print!("(behave) ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 || line.trim() == "quit" {
+            break;
+        }
+        println!("{}", debugger.execute(&line));
+    }
+}
@@ -0,0 +1,269 @@
+use behave::debug::{Client, NodeInfo, ServerMessage};
+use behave::observer::{TraceEvent, TraceRecord, TreeObserver};
+use behave::registry::{NodeRegistry, NodeSpec};
+use behave::{Status, Tree};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{env, fs, io, process, thread};
+
+const USAGE: &str = "usage: behave-tui <tree-file> [--interval <ms>] [--repeat]
+       behave-tui --connect <host:port | socket-path>";
+
+#[derive(Default)]
+struct TickCounter {
+    updates: Vec<usize>,
+}
+
+impl TreeObserver for TickCounter {
+    fn attach(&mut self, tree: &Tree) {
+        self.updates = vec![0; tree.node_count()];
+    }
+
+    fn notify(&mut self, record: &TraceRecord) {
+        if let TraceEvent::Update { node, .. } = record.event {
+            self.updates[node.0] += 1;
+        }
+    }
+}
+
+struct Row {
+    depth: usize,
+    name: String,
+    status: Status,
+    ticks: usize,
+}
+
+enum Source {
+    File {
+        path: String,
+        interval: Duration,
+        repeat: bool,
+    },
+    Connect(String),
+}
+
+fn parse_options() -> Result<Source, String> {
+    let mut args = env::args().skip(1);
+    let mut path = None;
+    let mut connect = None;
+    let mut interval = Duration::from_millis(250);
+    let mut repeat = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--interval" => {
+                let ms = args.next().and_then(|ms| ms.parse().ok());
+                interval = Duration::from_millis(ms.ok_or("--interval expects milliseconds")?);
+            }
+            "--repeat" => repeat = true,
+            "--connect" => connect = Some(args.next().ok_or("--connect expects an address")?),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    match (path, connect) {
+        (Some(path), None) => Ok(Source::File {
+            path,
+            interval,
+            repeat,
+        }),
+        (None, Some(addr)) => Ok(Source::Connect(addr)),
+        (Some(_), Some(_)) => Err("pass either a tree file or --connect".to_string()),
+        (None, None) => Err("missing tree file".to_string()),
+    }
+}
+
+fn color(status: Status) -> &'static str {
+    match status {
+        Status::Invalid => "\x1b[2m",
+        Status::Running => "\x1b[33m",
+        Status::Success => "\x1b[32m",
+        Status::Failure => "\x1b[31m",
+        Status::Aborted => "\x1b[35m",
+    }
+}
+
+fn render(header: &str, rows: &[Row], next: Option<usize>) {
+    let mut out = String::from("\x1b[2J\x1b[H");
+    writeln!(out, "{}\n", header).unwrap();
+    for (id, row) in rows.iter().enumerate() {
+        writeln!(
+            out,
+            "{} {:indent$}{}n{} {}\x1b[0m  {:?}  ticks {}",
+            if next == Some(id) { '>' } else { ' ' },
+            "",
+            color(row.status),
+            id,
+            row.name,
+            row.status,
+            row.ticks,
+            indent = row.depth * 2
+        )
+        .unwrap();
+    }
+    print!("{}", out);
+}
+
+fn load(path: &str) -> Result<Tree, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let spec = NodeSpec::parse(&source).map_err(|e| format!("{}: {}", path, e))?;
+    let builder = NodeRegistry::new()
+        .instantiate(&spec)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    let tree = Tree::try_new(builder).map_err(|e| format!("{}: {}", path, e))?;
+    let errors: Vec<String> = tree
+        .validate()
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .map(|diagnostic| format!("{}: {}", path, diagnostic))
+        .collect();
+    if errors.is_empty() {
+        Ok(tree)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+fn run_file(path: &str, interval: Duration, repeat: bool) -> Result<(), String> {
+    let mut tree = load(path)?;
+    let counter = Arc::new(Mutex::new(TickCounter::default()));
+    tree.add_observer(counter.clone());
+    let show = |tree: &Tree, run: usize| {
+        let counter = counter.lock().unwrap();
+        let mut rows = vec![];
+        tree.walk(|visit| {
+            let node = visit.node;
+            rows.push(Row {
+                depth: visit.depth,
+                name: node.name().to_string(),
+                status: node.status(),
+                ticks: counter.updates[visit.id.0],
+            });
+        });
+        let header = format!(
+            "{}  run {}  step {}  root {:?}",
+            path,
+            run,
+            tree.steps(),
+            tree.status()
+        );
+        render(&header, &rows, tree.pending().first().map(|id| id.0));
+    };
+
+    let mut run = 0;
+    loop {
+        run += 1;
+        tree.start();
+        loop {
+            show(&tree, run);
+            if !tree.step() {
+                break;
+            }
+            thread::sleep(interval);
+        }
+        show(&tree, run);
+        if !repeat {
+            return Ok(());
+        }
+        thread::sleep(interval * 4);
+    }
+}
+
+fn rows_from(nodes: &[NodeInfo]) -> Vec<Row> {
+    let mut rows: Vec<Row> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let depth = node
+            .parent
+            .and_then(|parent| rows.get(parent.0))
+            .map_or(0, |parent| parent.depth + 1);
+        rows.push(Row {
+            depth,
+            name: node.name.clone(),
+            status: node.status,
+            ticks: 0,
+        });
+    }
+    rows
+}
+
+fn run_remote(addr: &str) -> io::Result<()> {
+    let mut client = if addr.contains('/') {
+        connect_unix(addr)?
+    } else {
+        Client::connect_tcp(addr)?
+    };
+    let mut rows = vec![];
+    let mut step = 0;
+    let mut state = String::from("running");
+    loop {
+        let mut next = None;
+        match client.recv()? {
+            ServerMessage::Tree { nodes } => rows = rows_from(&nodes),
+            ServerMessage::Update {
+                step: at,
+                node,
+                status,
+            } => {
+                step = at;
+                // Updates for nodes missing from the last `tree` message are dropped.
+                if let Some(row) = rows.get_mut(node.0) {
+                    row.ticks += 1;
+                    row.status = status;
+                }
+            }
+            ServerMessage::Transition {
+                step: at, node, to, ..
+            } => {
+                step = at;
+                if let Some(row) = rows.get_mut(node.0) {
+                    row.status = to;
+                }
+            }
+            ServerMessage::Paused { node } => {
+                state = format!("paused before n{}", node.0);
+                next = Some(node.0);
+            }
+            ServerMessage::Breakpoint { node, from, to } => {
+                state = format!("paused after n{} {:?} -> {:?}", node.0, from, to);
+            }
+            ServerMessage::Finished { status } => state = format!("finished {:?}", status),
+            ServerMessage::State { paused } => {
+                state = if paused { "paused" } else { "running" }.to_string()
+            }
+            ServerMessage::Ok | ServerMessage::Error { .. } => {}
+        }
+        render(&format!("{}  step {}  {}", addr, step, state), &rows, next);
+    }
+}
+
+#[cfg(unix)]
+fn connect_unix(path: &str) -> io::Result<Client> {
+    Client::connect_unix(path)
+}
+
+#[cfg(not(unix))]
+fn connect_unix(_: &str) -> io::Result<Client> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unix sockets are not supported on this platform",
+    ))
+}
+
+fn main() {
+    let source = parse_options().unwrap_or_else(|message| {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(2);
+    });
+    let result = match source {
+        Source::File {
+            path,
+            interval,
+            repeat,
+        } => run_file(&path, interval, repeat),
+        Source::Connect(addr) => run_remote(&addr).map_err(|e| format!("{}: {}", addr, e)),
+    };
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
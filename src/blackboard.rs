@@ -0,0 +1,239 @@
+use crate::registry::{Param, Params};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct ScopeId(pub usize);
+
+#[derive(Clone, Debug, Default)]
+pub struct Scope {
+    remap: Vec<(String, String)>,
+    inherit: bool,
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Every key the scope doesn't remap resolves to the same key outside.
+    pub fn inherit(mut self) -> Self {
+        self.inherit = true;
+        self
+    }
+
+    pub fn remap(mut self, inner: &str, outer: &str) -> Self {
+        let outer = outer.trim_start_matches('{').trim_end_matches('}');
+        self.remap.retain(|(key, _)| key != inner);
+        self.remap.push((inner.to_string(), outer.to_string()));
+        self
+    }
+}
+
+struct Layer {
+    parent: Option<ScopeId>,
+    scope: Scope,
+    values: HashMap<String, Param>,
+}
+
+pub struct Blackboard {
+    layers: Vec<Layer>,
+    changed: Vec<(ScopeId, String)>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self {
+            layers: vec![Layer {
+                parent: None,
+                scope: Scope::new(),
+                values: HashMap::new(),
+            }],
+            changed: vec![],
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Param> {
+        self.get_in(ScopeId(0), key)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn set(&mut self, key: &str, value: Param) {
+        self.set_in(ScopeId(0), key, value)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Param> {
+        self.remove_in(ScopeId(0), key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys_in(ScopeId(0))
+    }
+
+    pub fn get_in(&self, scope: ScopeId, key: &str) -> Option<&Param> {
+        let (scope, key) = self.resolve(scope, key);
+        self.layers[scope.0].values.get(&key)
+    }
+
+    pub fn set_in(&mut self, scope: ScopeId, key: &str, value: Param) {
+        let (scope, key) = self.resolve(scope, key);
+        let values = &mut self.layers[scope.0].values;
+        if values.get(&key) != Some(&value) {
+            values.insert(key.clone(), value);
+            self.changed(scope, key);
+        }
+    }
+
+    pub fn remove_in(&mut self, scope: ScopeId, key: &str) -> Option<Param> {
+        let (scope, key) = self.resolve(scope, key);
+        let removed = self.layers[scope.0].values.remove(&key);
+        if removed.is_some() {
+            self.changed(scope, key);
+        }
+        removed
+    }
+
+    // Only the keys stored in the scope itself, not those it reaches outside.
+    pub fn keys_in(&self, scope: ScopeId) -> impl Iterator<Item = &str> {
+        self.layers[scope.0].values.keys().map(String::as_str)
+    }
+
+    pub fn scope_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn resolve(&self, mut scope: ScopeId, key: &str) -> (ScopeId, String) {
+        let mut key = key.to_string();
+        loop {
+            let layer = &self.layers[scope.0];
+            let parent = match layer.parent {
+                Some(parent) => parent,
+                None => return (scope, key),
+            };
+            match layer.scope.remap.iter().find(|(inner, _)| *inner == key) {
+                Some((_, outer)) => key = outer.clone(),
+                None if layer.scope.inherit => {}
+                None => return (scope, key),
+            }
+            scope = parent;
+        }
+    }
+
+    pub(crate) fn add_scope(&mut self, parent: ScopeId, scope: Scope) -> ScopeId {
+        self.layers.push(Layer {
+            parent: Some(parent),
+            scope,
+            values: HashMap::new(),
+        });
+        ScopeId(self.layers.len() - 1)
+    }
+
+    pub(crate) fn save(&self) -> Vec<Params> {
+        self.layers
+            .iter()
+            .map(|layer| {
+                let mut values = Params::new();
+                for (key, value) in &layer.values {
+                    values.insert(key, value.clone());
+                }
+                values
+            })
+            .collect()
+    }
+
+    // Restored values don't count as changes, so nothing aborts on load.
+    pub(crate) fn load(&mut self, saved: &[Params]) {
+        for (layer, values) in self.layers.iter_mut().zip(saved) {
+            layer.values = values
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect();
+        }
+        self.changed.clear();
+    }
+
+    fn changed(&mut self, scope: ScopeId, key: String) {
+        if !self
+            .changed
+            .iter()
+            .any(|changed| changed.0 == scope && changed.1 == key)
+        {
+            self.changed.push((scope, key));
+        }
+    }
+
+    pub(crate) fn take_changed(&mut self) -> Vec<(ScopeId, String)> {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+impl Default for Blackboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// What nodes see through their `Context`: keys resolve from the node's scope.
+pub struct Scoped<B> {
+    blackboard: B,
+    scope: ScopeId,
+}
+
+impl<B: Deref<Target = Blackboard>> Scoped<B> {
+    pub(crate) fn new(blackboard: B, scope: ScopeId) -> Self {
+        Self { blackboard, scope }
+    }
+
+    pub fn scope(&self) -> ScopeId {
+        self.scope
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Param> {
+        self.blackboard.get_in(self.scope, key)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<B: DerefMut<Target = Blackboard>> Scoped<B> {
+    pub fn set(&mut self, key: &str, value: Param) {
+        self.blackboard.set_in(self.scope, key, value)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Param> {
+        self.blackboard.remove_in(self.scope, key)
+    }
+}
+
+// `SelfOnly` is what Unreal calls "Self": it aborts the decorator's own
+// subtree when the condition stops holding.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum AbortMode {
+    None,
+    SelfOnly,
+    LowerPriority,
+    Both,
+}
+
+impl AbortMode {
+    pub fn aborts_self(self) -> bool {
+        self == AbortMode::SelfOnly || self == AbortMode::Both
+    }
+
+    pub fn aborts_lower_priority(self) -> bool {
+        self == AbortMode::LowerPriority || self == AbortMode::Both
+    }
+}
+
+pub trait Observe {
+    fn key(&self) -> &str;
+
+    fn abort_mode(&self) -> AbortMode;
+
+    fn check(&self, value: Option<&Param>) -> bool;
+}
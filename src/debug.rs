@@ -0,0 +1,519 @@
+//! Remote debugging over a local socket.
+//!
+//! A [`Server`] owns a [`Debugger`] and talks to any number of clients using
+//! JSON lines: every message is a single JSON object terminated by `\n`.
+//!
+//! Server to client messages are tagged with `type`:
+//!
+//! - `{"type":"tree","nodes":[{"id":0,"parent":null,"kind":"Selector","name":"guard","status":"Invalid"}]}`
+//!   is sent on connect and in reply to the `tree` command.
+//! - `{"type":"update","step":4,"node":2,"status":"Running"}` for every node update.
+//! - `{"type":"transition","step":4,"node":2,"from":"Running","to":"Success"}` for every status change.
+//! - `{"type":"paused","node":5}` when a node breakpoint stops execution before `update`.
+//! - `{"type":"breakpoint","node":2,"from":"Running","to":"Failure"}` when a transition breakpoint is hit.
+//! - `{"type":"finished","status":"Success"}` when the root completes. The tree is not
+//!   run again until a client steps it or the host restarts it, unless
+//!   [`Server::restart_on_finish`] is enabled.
+//! - `{"type":"state","paused":true}` in reply to `pause`, `resume` and `step`.
+//! - `{"type":"ok"}` and `{"type":"error","message":"..."}` for the remaining commands.
+//!
+//! Client to server commands are tagged with `command`:
+//!
+//! - `{"command":"pause"}`, `{"command":"resume"}` and `{"command":"step"}`.
+//! - `{"command":"break","node":3}` pauses before node 3 updates, while
+//!   `{"command":"break","node":3,"to":"Failure"}` or `{"command":"break","to":"Failure"}`
+//!   pause after the matching status transition. `delete` takes the same fields.
+//! - `{"command":"tree"}` requests the structure and current statuses.
+use crate::debugger::{Breakpoint, Debugger, Stop};
+use crate::observer::TraceEvent;
+use crate::{NodeId, Status, Tree};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct NodeInfo {
+    pub id: NodeId,
+    pub parent: Option<NodeId>,
+    pub kind: String,
+    pub name: String,
+    pub status: Status,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Tree {
+        nodes: Vec<NodeInfo>,
+    },
+    Update {
+        step: usize,
+        node: NodeId,
+        status: Status,
+    },
+    Transition {
+        step: usize,
+        node: NodeId,
+        from: Status,
+        to: Status,
+    },
+    Paused {
+        node: NodeId,
+    },
+    Breakpoint {
+        node: NodeId,
+        from: Status,
+        to: Status,
+    },
+    Finished {
+        status: Status,
+    },
+    State {
+        paused: bool,
+    },
+    Ok,
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Pause,
+    Resume,
+    Step,
+    Break {
+        #[serde(default)]
+        node: Option<NodeId>,
+        #[serde(default)]
+        to: Option<Status>,
+    },
+    Delete {
+        #[serde(default)]
+        node: Option<NodeId>,
+        #[serde(default)]
+        to: Option<Status>,
+    },
+    Tree,
+}
+
+impl Command {
+    fn breakpoint(node: Option<NodeId>, to: Option<Status>) -> Option<Breakpoint> {
+        match (node, to) {
+            (Some(node), None) => Some(Breakpoint::Node(node)),
+            (node, Some(to)) => Some(Breakpoint::Transition { node, to }),
+            (None, None) => None,
+        }
+    }
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn try_clone(&self) -> io::Result<Stream> {
+        Ok(match self {
+            Stream::Tcp(stream) => Stream::Tcp(stream.try_clone()?),
+            #[cfg(unix)]
+            Stream::Unix(stream) => Stream::Unix(stream.try_clone()?),
+        })
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+// Clients that fall this far behind are dropped rather than stalling the tree.
+const MAX_OUTGOING: usize = 1 << 20;
+// Commands are short, so a longer unfinished line is a misbehaving client.
+const MAX_INCOMING: usize = 1 << 16;
+
+struct Connection {
+    stream: Stream,
+    pending: Vec<u8>,
+    outgoing: Vec<u8>,
+    closed: bool,
+}
+
+impl Connection {
+    fn new(stream: Stream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(Self {
+            stream,
+            pending: vec![],
+            outgoing: vec![],
+            closed: false,
+        })
+    }
+
+    fn send(&mut self, message: &ServerMessage) {
+        if self.closed {
+            return;
+        }
+        serde_json::to_writer(&mut self.outgoing, message).expect("server messages serialize");
+        self.outgoing.push(b'\n');
+        if self.outgoing.len() > MAX_OUTGOING {
+            self.closed = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        let mut written = 0;
+        while !self.closed && written < self.outgoing.len() {
+            match self.stream.write(&self.outgoing[written..]) {
+                Ok(0) => self.closed = true,
+                Ok(count) => written += count,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.closed = true,
+            }
+        }
+        self.outgoing.drain(..written);
+    }
+
+    fn read_lines(&mut self) -> Vec<String> {
+        let mut buf = [0; 1024];
+        while self.pending.len() <= MAX_INCOMING {
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(read) => self.pending.extend_from_slice(&buf[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+        let mut lines = vec![];
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&line).trim().to_string());
+        }
+        if self.pending.len() > MAX_INCOMING {
+            self.closed = true;
+        }
+        lines
+    }
+}
+
+pub struct Server {
+    listener: Listener,
+    connections: Vec<Connection>,
+    debugger: Debugger,
+    paused: bool,
+    finished: bool,
+    restart_on_finish: bool,
+}
+
+impl Server {
+    pub fn bind_tcp<A: ToSocketAddrs>(addr: A, tree: Tree) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(Listener::Tcp(listener), tree))
+    }
+
+    #[cfg(unix)]
+    pub fn bind_unix<P: AsRef<Path>>(path: P, tree: Tree) -> io::Result<Self> {
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(Listener::Unix(listener), tree))
+    }
+
+    fn new(listener: Listener, tree: Tree) -> Self {
+        Self {
+            listener,
+            connections: vec![],
+            debugger: Debugger::new(tree),
+            paused: false,
+            finished: false,
+            restart_on_finish: false,
+        }
+    }
+
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(_) => None,
+        }
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    pub fn tree(&self) -> &Tree {
+        self.debugger.tree()
+    }
+
+    // Lets the host set the blackboard, send events or drive the tree itself
+    // between polls.
+    pub fn tree_mut(&mut self) -> &mut Tree {
+        self.debugger.tree_mut()
+    }
+
+    // A finished tree is left alone until it is restarted, e.g. by a client's
+    // `step` or through `debugger_mut`, unless this is enabled.
+    pub fn restart_on_finish(&mut self, restart: bool) {
+        self.restart_on_finish = restart;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    // Never fails: a client that errors is dropped and the rest are served.
+    pub fn poll(&mut self) {
+        self.accept();
+        for index in 0..self.connections.len() {
+            for line in self.connections[index].read_lines() {
+                let reply = match serde_json::from_str::<Command>(&line) {
+                    Ok(command) => self.execute(command),
+                    Err(e) => ServerMessage::Error {
+                        message: e.to_string(),
+                    },
+                };
+                self.connections[index].send(&reply);
+            }
+        }
+        if !self.paused && !self.finished() {
+            self.finished = false;
+            let stop = self.debugger.advance();
+            self.stream_trace();
+            if let Some(stop) = stop {
+                self.report(stop);
+            }
+        }
+        for connection in &mut self.connections {
+            connection.flush();
+        }
+        self.connections.retain(|connection| !connection.closed);
+    }
+
+    fn accept(&mut self) {
+        loop {
+            let accepted = match &self.listener {
+                Listener::Tcp(listener) => listener.accept().map(|(s, _)| Stream::Tcp(s)),
+                #[cfg(unix)]
+                Listener::Unix(listener) => listener.accept().map(|(s, _)| Stream::Unix(s)),
+            };
+            match accepted {
+                // A socket that can't be set up only costs that one client.
+                Ok(stream) => {
+                    if let Ok(mut connection) = Connection::new(stream) {
+                        connection.send(&self.structure());
+                        self.connections.push(connection);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                // A client that hung up before it was accepted, or running out
+                // of descriptors, mustn't stop the host's loop. Whoever is still
+                // waiting is accepted on the next poll.
+                Err(_) => return,
+            }
+        }
+    }
+
+    // Restarting the tree from the host or a client's `step` makes it run again.
+    fn finished(&self) -> bool {
+        self.finished && !self.restart_on_finish && self.debugger.tree().status() != Status::Running
+    }
+
+    fn execute(&mut self, command: Command) -> ServerMessage {
+        match command {
+            Command::Pause => {
+                self.paused = true;
+                ServerMessage::State { paused: true }
+            }
+            Command::Resume => {
+                self.paused = false;
+                ServerMessage::State { paused: false }
+            }
+            Command::Step => {
+                self.paused = true;
+                let stop = self.debugger.step();
+                self.stream_trace();
+                if let Stop::Finished(_) = stop {
+                    self.report(stop);
+                }
+                ServerMessage::State { paused: true }
+            }
+            Command::Break { node, to } => match Command::breakpoint(node, to) {
+                Some(breakpoint) => {
+                    self.debugger.add_breakpoint(breakpoint);
+                    ServerMessage::Ok
+                }
+                None => missing_breakpoint(),
+            },
+            Command::Delete { node, to } => match Command::breakpoint(node, to) {
+                Some(breakpoint) if self.debugger.remove_breakpoint(breakpoint) => {
+                    ServerMessage::Ok
+                }
+                Some(_) => ServerMessage::Error {
+                    message: "no such breakpoint".to_string(),
+                },
+                None => missing_breakpoint(),
+            },
+            Command::Tree => self.structure(),
+        }
+    }
+
+    fn structure(&self) -> ServerMessage {
+        let mut nodes = vec![];
+        self.debugger.tree().walk(|visit| {
+            let node = visit.node;
+            nodes.push(NodeInfo {
+                id: visit.id,
+                parent: visit.parent,
+                kind: node.kind().to_string(),
+                name: node.name().to_string(),
+                status: node.status(),
+            });
+        });
+        ServerMessage::Tree { nodes }
+    }
+
+    fn stream_trace(&mut self) {
+        for record in self.debugger.last_records() {
+            let message = match record.event {
+                TraceEvent::Update { node, status, .. } => ServerMessage::Update {
+                    step: record.step,
+                    node,
+                    status,
+                },
+                TraceEvent::StatusChange { node, from, to } => ServerMessage::Transition {
+                    step: record.step,
+                    node,
+                    from,
+                    to,
+                },
+                _ => continue,
+            };
+            self.broadcast(&message);
+        }
+    }
+
+    fn report(&mut self, stop: Stop) {
+        let message = match stop {
+            Stop::BeforeUpdate(node) => ServerMessage::Paused { node },
+            Stop::Transition { node, from, to } => ServerMessage::Breakpoint { node, from, to },
+            Stop::Finished(status) => ServerMessage::Finished { status },
+            Stop::Stepped | Stop::Waiting => return,
+        };
+        match message {
+            ServerMessage::Paused { .. } | ServerMessage::Breakpoint { .. } => self.paused = true,
+            ServerMessage::Finished { .. } => self.finished = true,
+            _ => {}
+        }
+        self.broadcast(&message);
+    }
+
+    fn broadcast(&mut self, message: &ServerMessage) {
+        for connection in self.connections.iter_mut() {
+            connection.send(message);
+        }
+    }
+}
+
+fn missing_breakpoint() -> ServerMessage {
+    ServerMessage::Error {
+        message: "breakpoints need a `node`, a `to` status or both".to_string(),
+    }
+}
+
+pub struct Client {
+    reader: BufReader<Stream>,
+    writer: Stream,
+}
+
+impl Client {
+    pub fn connect_tcp<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::new(Stream::Tcp(TcpStream::connect(addr)?))
+    }
+
+    #[cfg(unix)]
+    pub fn connect_unix<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(Stream::Unix(UnixStream::connect(path)?))
+    }
+
+    fn new(stream: Stream) -> io::Result<Self> {
+        Ok(Self {
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+        })
+    }
+
+    pub fn send(&mut self, command: &Command) -> io::Result<()> {
+        let mut line = serde_json::to_vec(command)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)
+    }
+
+    pub fn recv(&mut self) -> io::Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        serde_json::from_str(&line).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+    }
+}
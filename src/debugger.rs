@@ -0,0 +1,292 @@
+use crate::observer::{Recorder, TraceEvent, TraceRecord};
+use crate::sync::{self, Handle, SharedCell};
+use crate::{NodeId, Status, Tree};
+use std::fmt::Write;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Breakpoint {
+    Node(NodeId),
+    Transition { node: Option<NodeId>, to: Status },
+}
+
+impl Breakpoint {
+    fn matches_transition(&self, event: &TraceEvent) -> bool {
+        match (*self, *event) {
+            (
+                Breakpoint::Transition { node, to },
+                TraceEvent::StatusChange {
+                    node: changed,
+                    to: status,
+                    ..
+                },
+            ) => status == to && node.is_none_or(|node| node == changed),
+            _ => false,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Stop {
+    BeforeUpdate(NodeId),
+    Transition {
+        node: NodeId,
+        from: Status,
+        to: Status,
+    },
+    Stepped,
+    // The queue is empty but nodes are still subscribed to events.
+    Waiting,
+    Finished(Status),
+}
+
+pub struct Debugger {
+    tree: Tree,
+    breakpoints: Vec<Breakpoint>,
+    trace: Handle<Recorder>,
+    paused_before: Option<NodeId>,
+    started: bool,
+}
+
+impl Debugger {
+    pub fn new(mut tree: Tree) -> Self {
+        let trace = sync::handle(Recorder::new());
+        tree.add_observer(trace.clone());
+        Self {
+            tree,
+            breakpoints: vec![],
+            trace,
+            paused_before: None,
+            started: false,
+        }
+    }
+
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    pub fn tree_mut(&mut self) -> &mut Tree {
+        &mut self.tree
+    }
+
+    pub fn into_tree(self) -> Tree {
+        self.tree
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|existing| *existing != breakpoint);
+        count != self.breakpoints.len()
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn restart(&mut self) {
+        self.trace.write(Recorder::clear);
+        self.paused_before = None;
+        self.tree.start();
+        self.started = true;
+    }
+
+    pub fn queue(&self) -> Vec<NodeId> {
+        self.tree.pending()
+    }
+
+    pub fn next_node(&self) -> Option<NodeId> {
+        self.queue().first().copied()
+    }
+
+    pub fn active_path(&self) -> Vec<NodeId> {
+        let mut path = vec![];
+        let mut current = self.next_node();
+        while let Some(id) = current {
+            path.push(id);
+            current = self.tree.parent(id);
+        }
+        path.reverse();
+        path
+    }
+
+    pub fn status(&self, id: NodeId) -> Option<Status> {
+        self.tree.node(id).map(|node| node.status())
+    }
+
+    pub fn step(&mut self) -> Stop {
+        self.begin();
+        self.paused_before = None;
+        if self.tree.step() && !self.tree.pending().is_empty() {
+            Stop::Stepped
+        } else {
+            self.finish()
+        }
+    }
+
+    pub fn notify(&mut self, event: &str) -> usize {
+        self.tree.notify(event)
+    }
+
+    pub fn resume(&mut self) -> Stop {
+        loop {
+            if let Some(stop) = self.advance() {
+                return stop;
+            }
+        }
+    }
+
+    pub fn advance(&mut self) -> Option<Stop> {
+        self.begin();
+        let next = match self.next_node() {
+            Some(next) => next,
+            None => return Some(self.finish()),
+        };
+        if self.paused_before != Some(next) && self.breakpoints.contains(&Breakpoint::Node(next)) {
+            self.paused_before = Some(next);
+            return Some(Stop::BeforeUpdate(next));
+        }
+        self.paused_before = None;
+        self.tree.step();
+        let breakpoints = &self.breakpoints;
+        self.trace.read(|trace| {
+            for record in trace.records.iter() {
+                if breakpoints
+                    .iter()
+                    .any(|b| b.matches_transition(&record.event))
+                {
+                    if let TraceEvent::StatusChange { node, from, to } = record.event {
+                        return Some(Stop::Transition { node, from, to });
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    pub fn last_records(&self) -> Vec<TraceRecord> {
+        self.trace.read(|trace| trace.records.clone())
+    }
+
+    pub fn execute(&mut self, command: &str) -> String {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words.as_slice() {
+            ["step"] | ["s"] => describe(self.step()),
+            ["continue"] | ["c"] => describe(self.resume()),
+            ["restart"] => {
+                self.restart();
+                "restarted".to_string()
+            }
+            ["break", target @ ..] | ["b", target @ ..] => match parse_breakpoint(target) {
+                Some(breakpoint) => {
+                    self.add_breakpoint(breakpoint);
+                    format!("breakpoint set: {:?}", breakpoint)
+                }
+                None => "usage: break <node> [status] | break * <status>".to_string(),
+            },
+            ["delete", target @ ..] => match parse_breakpoint(target) {
+                Some(breakpoint) if self.remove_breakpoint(breakpoint) => {
+                    format!("breakpoint removed: {:?}", breakpoint)
+                }
+                _ => "no such breakpoint".to_string(),
+            },
+            ["queue"] | ["q"] => join(&self.queue()),
+            ["path"] | ["p"] => join(&self.active_path()),
+            ["tree"] | ["t"] => self.render(),
+            _ => "commands: step, continue, restart, break, delete, queue, path, tree".to_string(),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let path = self.active_path();
+        let mut out = String::new();
+        self.tree.walk(|visit| {
+            let node = visit.node;
+            let marker = if path.last() == Some(&visit.id) {
+                ">"
+            } else if path.contains(&visit.id) {
+                "*"
+            } else {
+                " "
+            };
+            writeln!(
+                out,
+                "{} {:indent$}{} {} [{:?}]",
+                marker,
+                "",
+                visit.id,
+                node.name(),
+                node.status(),
+                indent = visit.depth * 2
+            )
+            .unwrap();
+        });
+        out
+    }
+
+    fn begin(&mut self) {
+        if self.started {
+            self.trace.write(Recorder::clear);
+        } else {
+            self.restart();
+        }
+    }
+
+    fn finish(&mut self) -> Stop {
+        if self.tree.status() == Status::Running && !self.tree.waiting().is_empty() {
+            return Stop::Waiting;
+        }
+        self.started = false;
+        Stop::Finished(self.tree.status())
+    }
+}
+
+fn parse_node(word: &str) -> Option<NodeId> {
+    word.trim_start_matches('n').parse().ok().map(NodeId)
+}
+
+fn parse_status(word: &str) -> Option<Status> {
+    match word.to_ascii_lowercase().as_str() {
+        "invalid" => Some(Status::Invalid),
+        "running" => Some(Status::Running),
+        "success" => Some(Status::Success),
+        "failure" => Some(Status::Failure),
+        "aborted" => Some(Status::Aborted),
+        _ => None,
+    }
+}
+
+fn parse_breakpoint(words: &[&str]) -> Option<Breakpoint> {
+    match words {
+        [node] => parse_node(node).map(Breakpoint::Node),
+        ["*", to] => parse_status(to).map(|to| Breakpoint::Transition { node: None, to }),
+        [node, to] => Some(Breakpoint::Transition {
+            node: Some(parse_node(node)?),
+            to: parse_status(to)?,
+        }),
+        _ => None,
+    }
+}
+
+fn describe(stop: Stop) -> String {
+    match stop {
+        Stop::BeforeUpdate(node) => format!("paused before updating {}", node),
+        Stop::Transition { node, from, to } => {
+            format!("paused after {} went {:?} -> {:?}", node, from, to)
+        }
+        Stop::Stepped => "stepped".to_string(),
+        Stop::Waiting => "waiting for an event".to_string(),
+        Stop::Finished(status) => format!("finished with {:?}", status),
+    }
+}
+
+fn join(ids: &[NodeId]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,194 @@
+use crate::{NodeId, Status, Tree};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Wake, Waker};
+
+#[derive(Default)]
+pub(crate) struct WakeQueue {
+    woken: Mutex<Vec<NodeId>>,
+    task: Mutex<Option<Waker>>,
+    events: Mutex<Vec<String>>,
+}
+
+impl WakeQueue {
+    pub(crate) fn take_events(&self) -> Vec<String> {
+        std::mem::take(&mut *lock(&self.events))
+    }
+
+    // The lock is released first: an executor that polls from inside `wake`
+    // registers its waker again.
+    fn wake_task(&self) {
+        let task = lock(&self.task).clone();
+        if let Some(task) = task {
+            task.wake();
+        }
+    }
+}
+
+// Like the shared cells, the queues stay usable after a panic elsewhere.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Sends events to a tree that is borrowed elsewhere, e.g. by `run_async`.
+// They are delivered on the tree's next tick or poll.
+#[derive(Clone)]
+pub struct Notifier {
+    queue: Arc<WakeQueue>,
+}
+
+impl Notifier {
+    pub(crate) fn new(queue: Arc<WakeQueue>) -> Self {
+        Self { queue }
+    }
+
+    pub fn notify(&self, event: &str) {
+        lock(&self.queue.events).push(event.to_string());
+        self.queue.wake_task();
+    }
+}
+
+struct NodeWaker {
+    node: NodeId,
+    queue: Arc<WakeQueue>,
+}
+
+impl Wake for NodeWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        lock(&self.queue.woken).push(self.node);
+        self.queue.wake_task();
+    }
+}
+
+pub(crate) struct Wakers {
+    queue: Arc<WakeQueue>,
+    wakers: Vec<Waker>,
+    parked: Vec<bool>,
+}
+
+impl Wakers {
+    pub(crate) fn new(nodes: usize, queue: Arc<WakeQueue>) -> Self {
+        let wakers = (0..nodes)
+            .map(|id| {
+                Waker::from(Arc::new(NodeWaker {
+                    node: NodeId(id),
+                    queue: queue.clone(),
+                }))
+            })
+            .collect();
+        Self {
+            queue,
+            wakers,
+            parked: vec![false; nodes],
+        }
+    }
+
+    pub(crate) fn waker(&self, id: NodeId) -> &Waker {
+        &self.wakers[id.0]
+    }
+
+    pub(crate) fn park(&mut self, id: NodeId) {
+        self.parked[id.0] = true;
+    }
+
+    pub(crate) fn unpark(&mut self, id: NodeId) {
+        self.parked[id.0] = false;
+    }
+
+    pub(crate) fn parked(&self) -> bool {
+        self.parked.contains(&true)
+    }
+
+    pub(crate) fn take_parked(&mut self) -> Vec<NodeId> {
+        (0..self.parked.len())
+            .filter(|id| std::mem::replace(&mut self.parked[*id], false))
+            .map(NodeId)
+            .collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.parked.iter_mut().for_each(|parked| *parked = false);
+        lock(&self.queue.woken).clear();
+    }
+
+    // Returns the parked nodes whose wakers fired since the last call.
+    pub(crate) fn woken(&mut self) -> Vec<NodeId> {
+        let woken = std::mem::take(&mut *lock(&self.queue.woken));
+        woken
+            .into_iter()
+            .filter(|id| std::mem::replace(&mut self.parked[id.0], false))
+            .collect()
+    }
+
+    fn register(&self, task: &Waker) {
+        let mut current = lock(&self.queue.task);
+        if !current
+            .as_ref()
+            .is_some_and(|current| current.will_wake(task))
+        {
+            *current = Some(task.clone());
+        }
+    }
+}
+
+pub struct RunAsync<'a> {
+    tree: &'a mut Tree,
+    started: bool,
+}
+
+impl<'a> RunAsync<'a> {
+    pub(crate) fn new(tree: &'a mut Tree) -> Self {
+        if tree.wakers.is_none() {
+            tree.wakers = Some(Wakers::new(tree.node_count(), tree.wake_queue.clone()));
+        }
+        Self {
+            tree,
+            started: false,
+        }
+    }
+}
+
+impl<'a> Future for RunAsync<'a> {
+    type Output = Status;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Status> {
+        if !self.started {
+            self.started = true;
+            self.tree.start();
+        }
+        let tree = &mut *self.tree;
+        if let Some(wakers) = &mut tree.wakers {
+            wakers.register(cx.waker());
+            tree.events.extend(wakers.woken());
+        }
+        tree.deliver_events();
+        tree.tick_queued();
+        if !tree.events.is_empty() {
+            // Give other tasks a turn between ticks.
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        } else if tree.wakers.as_ref().is_some_and(Wakers::parked) {
+            Poll::Pending
+        } else if tree.status() == Status::Running && !tree.waiting().is_empty() {
+            // Only a `Notifier` can reach the tree while it's borrowed here.
+            Poll::Pending
+        } else {
+            Poll::Ready(tree.status())
+        }
+    }
+}
+
+// Without wakers, parked nodes go back to being polled every tick.
+impl<'a> Drop for RunAsync<'a> {
+    fn drop(&mut self) {
+        if let Some(mut wakers) = self.tree.wakers.take() {
+            self.tree.events.extend(wakers.take_parked());
+        }
+        *lock(&self.tree.wake_queue.task) = None;
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{NodeId, Status, Tree};
+use std::fmt::Write;
+
+struct Entry {
+    id: NodeId,
+    label: String,
+    status: Status,
+    parent: Option<NodeId>,
+}
+
+impl Tree {
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph tree {\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\"];\n");
+        let entries = flatten(self);
+        for entry in entries.iter() {
+            writeln!(
+                out,
+                "    {} [label=\"{}\\n{:?}\", fillcolor=\"{}\"];",
+                entry.id,
+                escape_dot(&entry.label),
+                entry.status,
+                color(entry.status)
+            )
+            .unwrap();
+        }
+        for entry in entries.iter() {
+            if let Some(parent) = entry.parent {
+                writeln!(out, "    {} -> {};", parent, entry.id).unwrap();
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph TD\n");
+        let entries = flatten(self);
+        for entry in entries.iter() {
+            writeln!(
+                out,
+                "    {}[\"{}<br/>{:?}\"]:::{}",
+                entry.id,
+                escape_mermaid(&entry.label),
+                entry.status,
+                class(entry.status)
+            )
+            .unwrap();
+        }
+        for entry in entries.iter() {
+            if let Some(parent) = entry.parent {
+                writeln!(out, "    {} --> {}", parent, entry.id).unwrap();
+            }
+        }
+        for status in &[
+            Status::Invalid,
+            Status::Running,
+            Status::Success,
+            Status::Failure,
+            Status::Aborted,
+        ] {
+            writeln!(
+                out,
+                "    classDef {} fill:{}",
+                class(*status),
+                color(*status)
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+fn flatten(tree: &Tree) -> Vec<Entry> {
+    let mut entries = vec![];
+    tree.walk(|visit| {
+        let node = visit.node;
+        let label = if node.name() == node.kind() {
+            node.kind().to_string()
+        } else {
+            format!("{} ({})", node.name(), node.kind())
+        };
+        entries.push(Entry {
+            id: visit.id,
+            label,
+            status: node.status(),
+            parent: visit.parent,
+        });
+    });
+    entries
+}
+
+fn escape_dot(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Mermaid's entity codes; `#` itself has to be escaped since it starts one.
+fn escape_mermaid(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '&' => out.push_str("#amp;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '#' => out.push_str("#35;"),
+            '[' => out.push_str("#91;"),
+            ']' => out.push_str("#93;"),
+            '\n' => out.push_str("<br/>"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn class(status: Status) -> &'static str {
+    match status {
+        Status::Invalid => "invalid",
+        Status::Running => "running",
+        Status::Success => "success",
+        Status::Failure => "failure",
+        Status::Aborted => "aborted",
+    }
+}
+
+fn color(status: Status) -> &'static str {
+    match status {
+        Status::Invalid => "#ffffff",
+        Status::Running => "#ffd966",
+        Status::Success => "#93c47d",
+        Status::Failure => "#e06666",
+        Status::Aborted => "#b7b7b7",
+    }
+}
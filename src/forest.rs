@@ -0,0 +1,139 @@
+use crate::{Status, Tree};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Slots are reused, so a handle also carries the generation of the slot it
+// was issued for; handles to removed trees never match a later one.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct TreeId {
+    index: usize,
+    generation: u32,
+}
+
+struct Slot {
+    generation: u32,
+    tree: Option<Tree>,
+}
+
+impl Slot {
+    fn get(&self, generation: u32) -> Option<&Tree> {
+        self.tree.as_ref().filter(|_| self.generation == generation)
+    }
+
+    fn tick(&mut self, index: usize) -> Option<(TreeId, Status)> {
+        let generation = self.generation;
+        self.tree
+            .as_mut()
+            .map(|tree| (TreeId { index, generation }, tree.tick()))
+    }
+}
+
+#[derive(Default)]
+pub struct Forest {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+}
+
+impl Forest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, tree: Tree) -> TreeId {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.tree = Some(tree);
+                TreeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    tree: Some(tree),
+                });
+                TreeId {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: TreeId) -> Option<Tree> {
+        let slot = self.slots.get_mut(id.index)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let tree = slot.tree.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        Some(tree)
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, id: TreeId) -> Option<&Tree> {
+        self.slots.get(id.index)?.get(id.generation)
+    }
+
+    pub fn get_mut(&mut self, id: TreeId) -> Option<&mut Tree> {
+        let slot = self.slots.get_mut(id.index)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        slot.tree.as_mut()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TreeId, &Tree)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let id = TreeId {
+                index,
+                generation: slot.generation,
+            };
+            slot.tree.as_ref().map(|tree| (id, tree))
+        })
+    }
+
+    // Runs on the current rayon pool; use `ThreadPool::install` to pick another.
+    #[cfg(feature = "parallel")]
+    pub fn tick(&mut self) -> Vec<(TreeId, Status)> {
+        self.slots
+            .par_iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.tick(index))
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    pub fn tick(&mut self) -> Vec<(TreeId, Status)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.tick(index))
+            .collect()
+    }
+}
+
+impl std::iter::FromIterator<Tree> for Forest {
+    fn from_iter<I: IntoIterator<Item = Tree>>(iter: I) -> Self {
+        Self {
+            slots: iter
+                .into_iter()
+                .map(|tree| Slot {
+                    generation: 0,
+                    tree: Some(tree),
+                })
+                .collect(),
+            free: vec![],
+        }
+    }
+}
@@ -1,60 +1,87 @@
+use crate::blackboard::{AbortMode, Scope};
 use crate::nodes::*;
-use crate::{Behavior, CustomBehavior, Node, NodeBuilder, Status};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::registry::Param;
+use crate::sync::{MaybeSend, SharedCell};
+use crate::{CustomBehavior, NodeBuilder, Status};
+use std::future::Future;
 
-pub fn action<T>(update: T) -> Box<NodeBuilder>
+pub fn action<T>(update: T) -> NodeBuilder
 where
-    T: FnMut() -> Status + Copy + 'static,
+    T: FnMut() -> Status + Copy + MaybeSend + 'static,
 {
-    Box::new(move |on_complete| -> Node {
-        Rc::new(RefCell::new(Action::new(Box::new(update), on_complete)))
-    })
+    NodeBuilder::new(Action::new(Box::new(update)))
 }
 
-pub fn custom<B>(behavior: Rc<RefCell<B>>) -> Box<NodeBuilder>
+pub fn async_action<F, Fut>(start: F) -> NodeBuilder
 where
-    B: CustomBehavior + 'static,
+    F: FnMut() -> Fut + MaybeSend + 'static,
+    Fut: Future + MaybeSend + 'static,
+    Fut::Output: IntoStatus,
 {
-    Box::new(move |on_complete| -> Node {
-        behavior.borrow_mut().set_on_complete(on_complete);
-        behavior
-    })
+    NodeBuilder::new(AsyncAction::new(start))
 }
 
-pub fn sequence(children_builder: Vec<Box<NodeBuilder>>) -> Box<NodeBuilder> {
-    Box::new(move |on_complete| -> Node {
-        let sequence = Rc::new(RefCell::new(Sequence::new(on_complete)));
-        let mut children = Vec::with_capacity(children_builder.len());
-        for child_builder in children_builder.into_iter() {
-            let seq = sequence.clone();
-            children.push((child_builder)(Some(Box::new(move |status, events| {
-                seq.borrow_mut().child_complete(status, events);
-            }))));
-        }
-        sequence.borrow_mut().children = children;
-        sequence
-    })
+// Takes an `Rc<RefCell<_>>`, or an `Arc<Mutex<_>>` with the `send` feature.
+pub fn custom<P>(behavior: P) -> NodeBuilder
+where
+    P: SharedCell + MaybeSend,
+    P::Target: CustomBehavior,
+{
+    NodeBuilder::new(Shared::new(behavior)).with_ports(P::Target::ports())
 }
 
-pub fn selector(children_builder: Vec<Box<NodeBuilder>>) -> Box<NodeBuilder> {
-    Box::new(move |on_complete| -> Node {
-        let selector = Rc::new(RefCell::new(Selector::new(on_complete)));
-        let mut children = Vec::with_capacity(children_builder.len());
-        for child_builder in children_builder.into_iter() {
-            let sel = selector.clone();
-            children.push((child_builder)(Some(Box::new(move |status, events| {
-                sel.borrow_mut().child_complete(status, events);
-            }))));
+pub fn sequence(children_builder: Vec<NodeBuilder>) -> NodeBuilder {
+    NodeBuilder::new(Sequence::new()).with_children(children_builder)
+}
+
+pub fn selector(children_builder: Vec<NodeBuilder>) -> NodeBuilder {
+    NodeBuilder::new(Selector::new()).with_children(children_builder)
+}
+
+pub fn repeater(node_builder: NodeBuilder, repeat_count: i32) -> NodeBuilder {
+    NodeBuilder::new(Repeater::new(repeat_count)).with_children(vec![node_builder])
+}
+
+pub fn wait(ticks: u32) -> NodeBuilder {
+    NodeBuilder::new(Wait::new(ticks))
+}
+
+pub fn wait_for(event: &str) -> NodeBuilder {
+    NodeBuilder::new(WaitFor::new(event))
+}
+
+pub fn condition<T>(check: T) -> NodeBuilder
+where
+    T: Fn() -> bool + Copy + MaybeSend + 'static,
+{
+    action(move || {
+        if check() {
+            Status::Success
+        } else {
+            Status::Failure
         }
-        selector.borrow_mut().children = children;
-        selector
     })
 }
 
-pub fn repeater(node_builder: Box<NodeBuilder>, repeat_count: i32) -> Box<NodeBuilder> {
-    Box::new(move |on_complete| -> Node {
-        let node = node_builder(None);
-        Rc::new(RefCell::new(Repeater::new(node, repeat_count, on_complete)))
-    })
+pub fn blackboard_condition<F>(
+    key: &str,
+    abort_mode: AbortMode,
+    check: F,
+    node_builder: NodeBuilder,
+) -> NodeBuilder
+where
+    F: Fn(Option<&Param>) -> bool + MaybeSend + 'static,
+{
+    NodeBuilder::new(BlackboardCondition::new(key, abort_mode, check))
+        .with_children(vec![node_builder])
+}
+
+pub fn subtree(scope: Scope, node_builder: NodeBuilder) -> NodeBuilder {
+    NodeBuilder::new(Subtree::new())
+        .with_children(vec![node_builder])
+        .scoped(scope)
+}
+
+pub fn named(name: &str, node_builder: NodeBuilder) -> NodeBuilder {
+    node_builder.named(name)
 }
@@ -1,75 +1,995 @@
-use std::cell::RefCell;
-use std::collections::VecDeque;
-use std::rc::Rc;
+pub use behave_derive::Behavior;
+use blackboard::{AbortMode, Blackboard, Observe, Scope, ScopeId, Scoped};
+use driver::{Notifier, RunAsync, WakeQueue, Wakers};
+use observer::{TraceEvent, TraceRecord, TreeObserver};
+use persist::Persist;
+use ports::{Binding, BoundPort, Direction, PortError, PortSpec, PortType, Ports};
+use profiler::{ProfileReport, Profiler};
+use registry::{Param, ParamError};
+use replay::{ExecutionLog, Replay};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::sync::Arc;
+use std::task::Waker;
+use std::time::Instant;
+use sync::{Handle, MaybeSend, SharedCell};
+use validate::{Diagnostic, Problem};
+#[macro_use]
+mod macros;
+
+pub mod blackboard;
+#[cfg(feature = "debug-server")]
+pub mod debug;
+pub mod debugger;
+pub mod driver;
+mod export;
+pub mod forest;
 pub mod helpers;
 pub mod nodes;
+pub mod observer;
+pub mod persist;
+pub mod ports;
+pub mod profiler;
+pub mod registry;
+pub mod replay;
+pub mod sync;
+pub mod validate;
+
+pub type Node = Box<dyn Behavior>;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug, Default)]
+pub struct NodeId(pub usize);
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "n{}", self.0)
+    }
+}
+
+pub struct NodeBuilder {
+    behavior: Node,
+    children: Vec<NodeBuilder>,
+    ports: Ports,
+    remap: Vec<(String, String)>,
+    scope: Option<Scope>,
+}
+
+impl NodeBuilder {
+    pub fn new<B>(behavior: B) -> Self
+    where
+        B: Behavior + 'static,
+    {
+        Self {
+            behavior: Box::new(behavior),
+            children: vec![],
+            ports: Ports::new(),
+            remap: vec![],
+            scope: None,
+        }
+    }
+
+    pub fn custom<B>(behavior: B) -> Self
+    where
+        B: CustomBehavior + 'static,
+    {
+        Self::new(behavior).with_ports(B::ports())
+    }
+
+    pub fn with_children(mut self, children: Vec<NodeBuilder>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn named(mut self, name: &str) -> Self {
+        self.behavior.set_name(name);
+        self
+    }
+
+    pub fn with_ports(mut self, ports: Ports) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    pub fn port(mut self, name: &str, value: &str) -> Self {
+        self.remap.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn ports(&self) -> &Ports {
+        &self.ports
+    }
+
+    // The node and everything below it see the blackboard through `scope`.
+    pub fn scoped(mut self, scope: Scope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+}
+
+enum Command {
+    Start(NodeId),
+    Schedule,
+    Subscribe(String),
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum Park {
+    No,
+    Waker,
+    Event,
+}
+
+pub struct Context<'a> {
+    node: NodeId,
+    children: &'a [NodeId],
+    commands: Vec<Command>,
+    waker: Option<&'a Waker>,
+    notified: Option<String>,
+    blackboard: &'a mut Blackboard,
+    scope: ScopeId,
+    ports: &'a [BoundPort],
+    parked: Park,
+}
+
+impl<'a> Context<'a> {
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.children.len()
+    }
+
+    pub fn start_child(&mut self, index: usize) -> bool {
+        match self.children.get(index) {
+            Some(child) => {
+                self.commands.push(Command::Start(*child));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn schedule(&mut self) {
+        self.commands.push(Command::Schedule);
+    }
+
+    // Running leaves that take a waker under `run_async` are only polled
+    // again once it fires.
+    pub fn waker(&mut self) -> Waker {
+        match self.waker {
+            Some(waker) => {
+                if self.parked == Park::No {
+                    self.parked = Park::Waker;
+                }
+                waker.clone()
+            }
+            None => Waker::noop().clone(),
+        }
+    }
+
+    // A running leaf that subscribes stays off the queue until the event is
+    // sent with `Tree::notify`.
+    pub fn subscribe(&mut self, event: &str) {
+        self.parked = Park::Event;
+        self.commands.push(Command::Subscribe(event.to_string()));
+    }
+
+    pub fn notified(&self) -> Option<&str> {
+        self.notified.as_deref()
+    }
+
+    pub fn blackboard(&self) -> Scoped<&Blackboard> {
+        Scoped::new(self.blackboard, self.scope)
+    }
+
+    pub fn blackboard_mut(&mut self) -> Scoped<&mut Blackboard> {
+        Scoped::new(self.blackboard, self.scope)
+    }
+
+    pub fn input<T: PortType>(&self, port: &str) -> Result<T, ParamError> {
+        let value = match self.binding(port, Direction::Input)?.1 {
+            Binding::Key(key) => self
+                .blackboard
+                .get_in(self.scope, key)
+                .ok_or_else(|| ParamError::Missing(key.clone()))?,
+            Binding::Value(value) => value,
+        };
+        T::from_param(value).ok_or_else(|| ParamError::WrongType {
+            name: port.to_string(),
+            expected: T::TYPE_NAME,
+            found: value.type_name(),
+        })
+    }
+
+    pub fn output<T: PortType>(&mut self, port: &str, value: T) -> Result<(), ParamError> {
+        let (spec, binding) = self.binding(port, Direction::Output)?;
+        if spec.type_name != T::TYPE_NAME {
+            return Err(ParamError::WrongType {
+                name: port.to_string(),
+                expected: spec.type_name,
+                found: T::TYPE_NAME,
+            });
+        }
+        match binding {
+            Binding::Key(key) => {
+                let key = key.clone();
+                self.blackboard.set_in(self.scope, &key, value.into_param());
+                Ok(())
+            }
+            Binding::Value(_) => Err(ParamError::Missing(port.to_string())),
+        }
+    }
+
+    fn binding(
+        &self,
+        port: &str,
+        direction: Direction,
+    ) -> Result<(&'a PortSpec, &'a Binding), ParamError> {
+        self.ports
+            .iter()
+            .find(|bound| bound.spec.name == port && bound.spec.direction == direction)
+            .and_then(|bound| Some((&bound.spec, bound.binding.as_ref()?)))
+            .ok_or_else(|| ParamError::Missing(port.to_string()))
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum BuildError {
+    Port(PortError),
+    SharedNode { first: NodeId, second: NodeId },
+    UnboundPorts(Vec<Diagnostic>),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BuildError::Port(error) => write!(f, "{}", error),
+            BuildError::SharedNode { first, second } => write!(
+                f,
+                "nodes {} and {} are the same shared instance",
+                first, second
+            ),
+            BuildError::UnboundPorts(diagnostics) => {
+                let lines: Vec<String> = diagnostics.iter().map(Diagnostic::to_string).collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<PortError> for BuildError {
+    fn from(error: PortError) -> Self {
+        BuildError::Port(error)
+    }
+}
+
+pub struct Visit<'a> {
+    pub id: NodeId,
+    pub parent: Option<NodeId>,
+    pub depth: usize,
+    pub node: &'a dyn Behavior,
+}
 
-pub type FnOnComplete = Option<Box<dyn FnMut(Status, &mut VecDeque<Node>) + 'static>>;
-pub type Node = Rc<RefCell<dyn Behavior>>;
-type NodeBuilder = dyn FnOnce(FnOnComplete) -> Node;
 pub struct Tree {
-    events: VecDeque<Node>,
-    root: Node,
+    events: VecDeque<NodeId>,
+    nodes: Vec<Node>,
+    parents: Vec<Option<NodeId>>,
+    children: Vec<Vec<NodeId>>,
+    observers: Vec<Box<dyn TreeObserver>>,
+    replay: Option<Replay>,
+    profiler: Option<Handle<Profiler>>,
+    wakers: Option<Wakers>,
+    wake_queue: Arc<WakeQueue>,
+    subscriptions: HashMap<String, Vec<NodeId>>,
+    waiting: Vec<bool>,
+    started: Vec<bool>,
+    notified: Vec<Option<String>>,
+    blackboard: Blackboard,
+    ports: Vec<Vec<BoundPort>>,
+    scopes: Vec<ScopeId>,
+    observed: Vec<Option<bool>>,
+    steps: usize,
 }
 
 impl Tree {
-    pub fn new(tree_builder: Box<NodeBuilder>) -> Self {
-        Self {
-            events: VecDeque::<Node>::new(),
-            root: tree_builder(None),
+    pub fn new(tree_builder: NodeBuilder) -> Self {
+        let tree = Self::try_new(tree_builder).unwrap_or_else(|error| panic!("{}", error));
+        if cfg!(debug_assertions) {
+            let errors: Vec<String> = tree
+                .validate()
+                .iter()
+                .filter(|diagnostic| diagnostic.is_error())
+                .map(Diagnostic::to_string)
+                .collect();
+            assert!(errors.is_empty(), "invalid tree:\n{}", errors.join("\n"));
+        }
+        tree
+    }
+
+    pub fn try_new(tree_builder: NodeBuilder) -> Result<Self, BuildError> {
+        let mut tree = Self {
+            events: VecDeque::new(),
+            nodes: vec![],
+            parents: vec![],
+            children: vec![],
+            observers: vec![],
+            replay: None,
+            profiler: None,
+            wakers: None,
+            wake_queue: Arc::default(),
+            subscriptions: HashMap::new(),
+            waiting: vec![],
+            started: vec![],
+            notified: vec![],
+            blackboard: Blackboard::new(),
+            ports: vec![],
+            scopes: vec![],
+            observed: vec![],
+            steps: 0,
+        };
+        tree.insert(tree_builder, None, ScopeId(0))?;
+        tree.check_instances()?;
+        tree.check_ports()?;
+        tree.observe_all();
+        Ok(tree)
+    }
+
+    // One instance in two places would have both parents driving the same
+    // status, so each shared node may only appear once per tree.
+    fn check_instances(&self) -> Result<(), BuildError> {
+        let mut seen = HashMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if let Some(instance) = node.instance() {
+                if let Some(first) = seen.insert(instance, NodeId(index)) {
+                    return Err(BuildError::SharedNode {
+                        first,
+                        second: NodeId(index),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Ports without a default have to be bound, outputs included. Every one
+    // that isn't is reported at once.
+    fn check_ports(&self) -> Result<(), BuildError> {
+        let unbound: Vec<Diagnostic> = self
+            .validate()
+            .into_iter()
+            .filter(|diagnostic| matches!(diagnostic.problem, Problem::UnboundPort(_)))
+            .collect();
+        if unbound.is_empty() {
+            Ok(())
+        } else {
+            Err(BuildError::UnboundPorts(unbound))
+        }
+    }
+
+    fn insert(
+        &mut self,
+        builder: NodeBuilder,
+        parent: Option<NodeId>,
+        scope: ScopeId,
+    ) -> Result<NodeId, PortError> {
+        let id = NodeId(self.nodes.len());
+        let scope = match builder.scope {
+            Some(inner) => self.blackboard.add_scope(scope, inner),
+            None => scope,
+        };
+        self.scopes.push(scope);
+        self.observed.push(None);
+        self.ports
+            .push(ports::bind(id, &builder.ports, builder.remap)?);
+        self.nodes.push(builder.behavior);
+        self.parents.push(parent);
+        self.children.push(vec![]);
+        self.waiting.push(false);
+        self.started.push(false);
+        self.notified.push(None);
+        for child in builder.children {
+            let child = self.insert(child, Some(id), scope)?;
+            self.children[id.0].push(child);
+        }
+        Ok(id)
+    }
+
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        let mut names: HashMap<&str, NodeId> = HashMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let id = NodeId(index);
+            let mut problems = node.diagnose(self.children[index].len());
+            if node.name() != node.kind() && names.insert(node.name(), id).is_some() {
+                problems.push(Problem::DuplicateName(node.name().to_string()));
+            }
+            for bound in &self.ports[index] {
+                if bound.binding.is_none() {
+                    problems.push(Problem::UnboundPort(bound.spec.name.clone()));
+                }
+            }
+            diagnostics.extend(
+                problems
+                    .into_iter()
+                    .map(|problem| Diagnostic { node: id, problem }),
+            );
+        }
+        diagnostics
+    }
+
+    pub fn add_observer<O>(&mut self, mut observer: O)
+    where
+        O: TreeObserver + 'static,
+    {
+        observer.attach(self);
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn enable_profiling(&mut self) {
+        if self.profiler.is_none() {
+            let profiler = sync::handle(Profiler::new());
+            self.add_observer(profiler.clone());
+            self.profiler = Some(profiler);
         }
     }
 
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.profiler
+            .as_ref()
+            .map(|profiler| profiler.read(Profiler::report))
+    }
+
+    pub fn replay(&mut self, log: ExecutionLog) {
+        self.replay = Some(Replay::new(log, self.steps));
+    }
+
+    pub fn stop_replay(&mut self) -> Option<Replay> {
+        self.replay.take()
+    }
+
+    pub fn replay_divergence(&self) -> Option<(usize, NodeId)> {
+        self.replay.as_ref().and_then(|replay| replay.divergence)
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&dyn Behavior> {
+        self.nodes.get(id.0).map(|node| node.as_ref())
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut (dyn Behavior + 'static)> {
+        self.nodes.get_mut(id.0).map(|node| node.as_mut())
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.parents.get(id.0).copied().flatten()
+    }
+
+    pub fn scope(&self, id: NodeId) -> Option<ScopeId> {
+        self.scopes.get(id.0).copied()
+    }
+
+    pub fn ports(&self, id: NodeId) -> &[BoundPort] {
+        self.ports.get(id.0).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.children.get(id.0).map(Vec::as_slice).unwrap_or(&[])
+    }
+
     pub fn run(&mut self) -> Status {
-        self.events.clear();
-        self.events.push_back(self.root.clone());
-        self.root.borrow_mut().initialize(&mut self.events);
+        self.start();
         while self.step() {}
-        self.root.borrow().status()
+        self.status()
+    }
+
+    pub fn tick(&mut self) -> Status {
+        self.deliver_events();
+        if self.events.is_empty() && self.status() != Status::Running {
+            self.start();
+        }
+        self.tick_queued();
+        self.status()
+    }
+
+    // Nodes queued during this tick wait for the next one.
+    fn tick_queued(&mut self) {
+        for _ in 0..self.events.len() {
+            self.step();
+        }
+    }
+
+    pub fn run_async(&mut self) -> RunAsync<'_> {
+        RunAsync::new(self)
+    }
+
+    pub fn notify(&mut self, event: &str) -> usize {
+        let subscribers = self.subscriptions.remove(event).unwrap_or_default();
+        let mut woken = 0;
+        for id in subscribers {
+            if std::mem::replace(&mut self.waiting[id.0], false) {
+                self.notified[id.0] = Some(event.to_string());
+                self.events.push_back(id);
+                woken += 1;
+            }
+        }
+        woken
+    }
+
+    pub fn notifier(&self) -> Notifier {
+        Notifier::new(self.wake_queue.clone())
+    }
+
+    fn deliver_events(&mut self) {
+        for event in self.wake_queue.take_events() {
+            self.notify(&event);
+        }
+    }
+
+    pub fn waiting(&self) -> Vec<NodeId> {
+        (0..self.nodes.len())
+            .filter(|id| self.waiting[*id])
+            .map(NodeId)
+            .collect()
+    }
+
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    // Observer decorators react to the change straight away.
+    pub fn set_blackboard(&mut self, key: &str, value: Param) {
+        self.blackboard.set(key, value);
+        self.apply_blackboard();
+    }
+
+    pub fn start(&mut self) {
+        self.events.clear();
+        self.started.iter_mut().for_each(|started| *started = false);
+        self.blackboard.take_changed();
+        self.observe_all();
+        self.subscriptions.clear();
+        self.waiting.iter_mut().for_each(|waiting| *waiting = false);
+        if let Some(wakers) = &mut self.wakers {
+            wakers.clear();
+        }
+        let before = self.snapshot_statuses();
+        self.start_node(NodeId(0));
+        let scheduled = self.events.len();
+        self.notify_scheduled(0..scheduled, &before);
+    }
+
+    pub fn status(&self) -> Status {
+        self.nodes[0].status()
+    }
+
+    pub fn pending(&self) -> Vec<NodeId> {
+        self.events.iter().copied().collect()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn walk<F>(&self, mut visitor: F)
+    where
+        F: FnMut(&Visit),
+    {
+        let mut stack = vec![(NodeId(0), 0)];
+        while let Some((id, depth)) = stack.pop() {
+            visitor(&Visit {
+                id,
+                parent: self.parent(id),
+                depth,
+                node: self.nodes[id.0].as_ref(),
+            });
+            for child in self.children[id.0].iter().rev() {
+                stack.push((*child, depth + 1));
+            }
+        }
     }
 
     pub fn step(&mut self) -> bool {
-        if let Some(node_rc) = self.events.pop_front() {
-            let mut node = node_rc.borrow_mut();
-            if node.status() == Status::Aborted {
+        if let Some(id) = self.events.pop_front() {
+            // Nodes aborted while queued are dropped, unless they were
+            // started again since.
+            let started = std::mem::replace(&mut self.started[id.0], false);
+            if !started && self.nodes[id.0].status() == Status::Aborted {
                 return true;
             }
-            let status = node.update(&mut self.events);
-            if status == Status::Failure || status == Status::Success {
-                node.on_complete(status, &mut self.events);
-            } else if status == Status::Running {
-                drop(node);
-                self.events.push_back(node_rc);
+            self.steps += 1;
+            let before = self.snapshot_statuses();
+            let queued = self.events.len();
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!(
+                "node",
+                id = ?id,
+                kind = self.nodes[id.0].kind(),
+                name = self.nodes[id.0].name()
+            )
+            .entered();
+            let previous = self.nodes[id.0].status();
+            let leaf = self.children[id.0].is_empty();
+            let replayed = match &mut self.replay {
+                Some(replay) if leaf => Some(replay.result(self.steps, id)),
+                _ => None,
+            };
+            let timer = (!self.observers.is_empty()).then(Instant::now);
+            let (status, parked) = match replayed {
+                // Settled like a completed result, since the leaf doesn't run.
+                Some(Status::Running) => {
+                    self.nodes[id.0].on_complete(Status::Running);
+                    (Status::Running, Park::No)
+                }
+                Some(status) => (status, Park::No),
+                None => self.call_parking(id, |node, context| node.update(context)),
+            };
+            let elapsed = timer.map(|timer| timer.elapsed()).unwrap_or_default();
+            if status != previous {
+                trace_event!(from = ?previous, to = ?status, "status changed");
             }
+            let completed = if status == Status::Failure || status == Status::Success {
+                self.complete(id, status)
+            } else {
+                vec![]
+            };
+            // Composites advance through their children's completions, so only
+            // leaves need to be polled again while running.
+            let scheduled = self.events.len();
+            if status == Status::Running && leaf {
+                match (parked, &mut self.wakers) {
+                    (Park::Event, _) => self.waiting[id.0] = true,
+                    (Park::Waker, Some(wakers)) => wakers.park(id),
+                    _ => self.events.push_back(id),
+                }
+            }
+            if !self.observers.is_empty() {
+                self.trace(TraceEvent::Update {
+                    node: id,
+                    status,
+                    elapsed,
+                });
+                for event in completed {
+                    self.trace(event);
+                }
+                self.notify_scheduled(queued..scheduled, &before);
+            }
+            self.apply_blackboard();
             true
         } else {
             false
         }
     }
+
+    fn call<F, R>(&mut self, id: NodeId, f: F) -> R
+    where
+        F: FnOnce(&mut dyn Behavior, &mut Context) -> R,
+    {
+        self.call_parking(id, f).0
+    }
+
+    fn call_parking<F, R>(&mut self, id: NodeId, f: F) -> (R, Park)
+    where
+        F: FnOnce(&mut dyn Behavior, &mut Context) -> R,
+    {
+        let mut context = Context {
+            node: id,
+            children: &self.children[id.0],
+            commands: vec![],
+            waker: self.wakers.as_ref().map(|wakers| wakers.waker(id)),
+            notified: self.notified[id.0].take(),
+            blackboard: &mut self.blackboard,
+            scope: self.scopes[id.0],
+            ports: &self.ports[id.0],
+            parked: Park::No,
+        };
+        let result = f(self.nodes[id.0].as_mut(), &mut context);
+        let parked = context.parked;
+        for command in context.commands {
+            match command {
+                Command::Start(child) => self.start_node(child),
+                Command::Schedule => self.events.push_back(id),
+                Command::Subscribe(event) => {
+                    let subscribers = self.subscriptions.entry(event).or_default();
+                    if !subscribers.contains(&id) {
+                        subscribers.push(id);
+                    }
+                }
+            }
+        }
+        (result, parked)
+    }
+
+    fn start_node(&mut self, id: NodeId) {
+        self.waiting[id.0] = false;
+        self.started[id.0] = true;
+        self.events.push_back(id);
+        // Replayed leaves take their results from the log, so none of their
+        // code runs, `initialize` included. They take the status they had in
+        // the recorded run instead.
+        if let Some(replay) = &self.replay {
+            if self.children[id.0].is_empty() {
+                if let Some(status) = replay.status(self.steps, id) {
+                    self.nodes[id.0].on_complete(status);
+                }
+                return;
+            }
+        }
+        self.call(id, |node, context| node.initialize(context));
+    }
+
+    fn complete(&mut self, mut id: NodeId, mut status: Status) -> Vec<TraceEvent> {
+        let mut completed = vec![];
+        loop {
+            self.nodes[id.0].on_complete(status);
+            let parent = match self.parents[id.0] {
+                Some(parent) => parent,
+                None => return completed,
+            };
+            completed.push(TraceEvent::ChildComplete {
+                parent,
+                child: id,
+                status,
+            });
+            let index = self.children[parent.0]
+                .iter()
+                .position(|child| *child == id)
+                .unwrap_or_default();
+            let result = self.call(parent, |node, context| {
+                node.child_complete(index, status, context)
+            });
+            if result != Status::Failure && result != Status::Success {
+                return completed;
+            }
+            id = parent;
+            status = result;
+        }
+    }
+
+    fn apply_blackboard(&mut self) {
+        let changed = self.blackboard.take_changed();
+        if changed.is_empty() {
+            return;
+        }
+        let mut failed = vec![];
+        let mut restarted = vec![];
+        for id in 0..self.nodes.len() {
+            let key = match self.nodes[id].observe() {
+                Some(observe) => self.blackboard.resolve(self.scopes[id], observe.key()),
+                None => continue,
+            };
+            if !changed.contains(&key) {
+                continue;
+            }
+            let (mode, result) = match self.check_observer(id) {
+                Some(checked) => checked,
+                None => continue,
+            };
+            // Only a change in the condition's result interrupts anything.
+            if self.observed[id].replace(result) == Some(result) {
+                continue;
+            }
+            let id = NodeId(id);
+            if self.is_active(id) {
+                if !result && mode.aborts_self() {
+                    failed.push(id);
+                }
+            } else if result && mode.aborts_lower_priority() {
+                if let Some(parent) = self.lower_priority_running(id) {
+                    restarted.push(parent);
+                }
+            }
+        }
+        if failed.is_empty() && restarted.is_empty() {
+            return;
+        }
+
+        let before = self.snapshot_statuses();
+        for id in failed.iter().chain(restarted.iter()) {
+            self.abort_children(*id);
+        }
+        let queued = self.events.len();
+        let mut completed = vec![];
+        for id in failed {
+            // An enclosing restart may already have aborted this one.
+            if self.is_active(id) {
+                completed.extend(self.complete(id, Status::Failure));
+            }
+        }
+        for id in restarted {
+            self.start_node(id);
+        }
+        if !self.observers.is_empty() {
+            for event in completed {
+                self.trace(event);
+            }
+            let scheduled = self.events.len();
+            self.notify_scheduled(queued..scheduled, &before);
+        }
+    }
+
+    fn check_observer(&self, id: usize) -> Option<(AbortMode, bool)> {
+        let observe = self.nodes[id].observe()?;
+        let value = self.blackboard.get_in(self.scopes[id], observe.key());
+        Some((observe.abort_mode(), observe.check(value)))
+    }
+
+    pub(crate) fn observe_all(&mut self) {
+        for id in 0..self.nodes.len() {
+            self.observed[id] = self.check_observer(id).map(|(_, result)| result);
+        }
+    }
+
+    // Returns the selector to re-evaluate when a sibling after `id` is
+    // running. Priority only means something among a selector's children.
+    fn lower_priority_running(&self, id: NodeId) -> Option<NodeId> {
+        let parent = self.parents[id.0]?;
+        if !self.nodes[parent.0].is_selector() || self.nodes[parent.0].status() != Status::Running {
+            return None;
+        }
+        let siblings = &self.children[parent.0];
+        let index = siblings.iter().position(|child| *child == id)?;
+        siblings[index + 1..]
+            .iter()
+            .any(|sibling| self.is_active(*sibling))
+            .then_some(parent)
+    }
+
+    // Started nodes that haven't been updated yet may still report their
+    // previous status, so being queued counts as running too.
+    fn is_active(&self, id: NodeId) -> bool {
+        self.nodes[id.0].status() == Status::Running || self.events.contains(&id)
+    }
+
+    fn abort_children(&mut self, id: NodeId) {
+        let mut aborted = vec![];
+        let mut stack = self.children[id.0].clone();
+        while let Some(child) = stack.pop() {
+            if !self.is_active(child) {
+                continue;
+            }
+            // Not every node records its own abort, so settle it like a result.
+            let status = self.nodes[child.0].abort();
+            self.nodes[child.0].on_complete(status);
+            self.waiting[child.0] = false;
+            self.started[child.0] = false;
+            if let Some(wakers) = &mut self.wakers {
+                wakers.unpark(child);
+            }
+            aborted.push(child);
+            stack.extend(self.children[child.0].iter().copied());
+        }
+        self.events.retain(|queued| !aborted.contains(queued));
+        if !self.observers.is_empty() {
+            for node in aborted {
+                self.trace(TraceEvent::Abort { node });
+            }
+        }
+    }
+
+    fn snapshot_statuses(&self) -> Vec<Status> {
+        if self.observers.is_empty() {
+            return vec![];
+        }
+        self.nodes.iter().map(|node| node.status()).collect()
+    }
+
+    fn notify_scheduled(&mut self, scheduled: Range<usize>, before: &[Status]) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let scheduled: Vec<NodeId> = self.events.range(scheduled).copied().collect();
+        for id in scheduled {
+            self.trace(TraceEvent::Initialize { node: id });
+        }
+        for (id, from) in before.iter().enumerate() {
+            let to = self.nodes[id].status();
+            if *from != to {
+                self.trace(TraceEvent::StatusChange {
+                    node: NodeId(id),
+                    from: *from,
+                    to,
+                });
+            }
+        }
+    }
+
+    fn trace(&mut self, event: TraceEvent) {
+        let record = TraceRecord {
+            step: self.steps,
+            time: Instant::now(),
+            event,
+        };
+        for observer in self.observers.iter_mut() {
+            observer.notify(&record);
+        }
+    }
 }
 
-pub trait Behavior {
-    fn initialize(&mut self, _: &mut VecDeque<Node>) {}
+pub trait Behavior: MaybeSend {
+    fn initialize(&mut self, _: &mut Context) {}
 
-    fn update(&mut self, _: &mut VecDeque<Node>) -> Status {
+    fn update(&mut self, _: &mut Context) -> Status {
         self.status()
     }
 
     fn status(&self) -> Status;
 
-    fn child_complete(&mut self, _: Status, _: &mut VecDeque<Node>) {}
+    fn child_complete(&mut self, _: usize, _: Status, _: &mut Context) -> Status {
+        self.status()
+    }
 
-    fn on_complete(&mut self, result: Status, events: &mut VecDeque<Node>);
+    fn on_complete(&mut self, result: Status);
 
     fn abort(&mut self) -> Status {
         Status::Aborted
     }
+
+    fn kind(&self) -> &'static str {
+        short_type_name(std::any::type_name::<Self>())
+    }
+
+    fn name(&self) -> &str {
+        self.kind()
+    }
+
+    fn set_name(&mut self, _: &str) {}
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        None
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        None
+    }
+
+    fn observe(&self) -> Option<&dyn Observe> {
+        None
+    }
+
+    fn diagnose(&self, _: usize) -> Vec<Problem> {
+        vec![]
+    }
+
+    fn instance(&self) -> Option<*const ()> {
+        None
+    }
+
+    // Selectors try their children in priority order, so a higher priority
+    // condition becoming true can abort a lower priority child.
+    fn is_selector(&self) -> bool {
+        false
+    }
+}
+
+fn short_type_name(name: &'static str) -> &'static str {
+    let path = name.split('<').next().unwrap_or(name);
+    let start = path.rfind("::").map(|i| i + 2).unwrap_or(0);
+    &name[start..]
 }
 
 pub trait CustomBehavior: Behavior {
-    fn set_on_complete(&mut self, on_complete: FnOnComplete);
+    fn ports() -> Ports
+    where
+        Self: Sized,
+    {
+        Ports::new()
+    }
+}
+
+pub trait Update {
+    fn initialize(&mut self, _: &mut Context) {}
+
+    fn update(&mut self, context: &mut Context) -> Status;
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum Status {
     Invalid,
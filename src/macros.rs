@@ -0,0 +1,103 @@
+/// Builds a tree out of nested node declarations, expanding to the `helpers` builders.
+///
+/// ```
+/// use behave::*;
+///
+/// let is_hungry = || true;
+/// let eat = helpers::action(|| Status::Success);
+/// let mut tree = Tree::new(tree! {
+///     sequence {
+///         cond(is_hungry),
+///         selector { eat, action(|| Status::Failure) },
+///         repeater(2) { action(|| Status::Success) }
+///     }
+/// });
+/// assert_eq!(Status::Success, tree.run());
+/// ```
+///
+/// Decorators take exactly one child:
+///
+/// ```compile_fail
+/// use behave::*;
+///
+/// let tree = tree! {
+///     repeater(2) { action(|| Status::Success), action(|| Status::Success) }
+/// };
+/// ```
+#[macro_export]
+macro_rules! tree {
+    (@children [$($done:expr,)*]) => {
+        vec![$($done),*]
+    };
+    (@children [$($done:expr,)*] $kind:ident { $($body:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::tree!(@children [$($done,)* $crate::tree!($kind { $($body)* }),] $($($rest)*)?)
+    };
+    (@children [$($done:expr,)*] $kind:ident ( $($args:tt)* ) { $($body:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::tree!(@children [$($done,)* $crate::tree!($kind ( $($args)* ) { $($body)* }),] $($($rest)*)?)
+    };
+    (@children [$($done:expr,)*] $kind:ident ( $($args:tt)* ) $(, $($rest:tt)*)?) => {
+        $crate::tree!(@children [$($done,)* $crate::tree!($kind ( $($args)* )),] $($($rest)*)?)
+    };
+    (@children [$($done:expr,)*] $leaf:ident $(, $($rest:tt)*)?) => {
+        $crate::tree!(@children [$($done,)* $leaf,] $($($rest)*)?)
+    };
+    (@children [$($done:expr,)*] $($other:tt)*) => {
+        compile_error!(concat!("expected a node but found `", stringify!($($other)*), "`"))
+    };
+
+    (@single $decorator:ident $kind:ident { $($body:tt)* } $(,)?) => {
+        $crate::tree!($kind { $($body)* })
+    };
+    (@single $decorator:ident $kind:ident ( $($args:tt)* ) { $($body:tt)* } $(,)?) => {
+        $crate::tree!($kind ( $($args)* ) { $($body)* })
+    };
+    (@single $decorator:ident $kind:ident ( $($args:tt)* ) $(,)?) => {
+        $crate::tree!($kind ( $($args)* ))
+    };
+    (@single $decorator:ident $leaf:ident $(,)?) => {
+        $leaf
+    };
+    (@single $decorator:ident $($other:tt)*) => {
+        compile_error!(concat!("`", stringify!($decorator), "` requires exactly one child"))
+    };
+
+    (sequence { $($body:tt)* }) => {
+        $crate::helpers::sequence($crate::tree!(@children [] $($body)*))
+    };
+    (selector { $($body:tt)* }) => {
+        $crate::helpers::selector($crate::tree!(@children [] $($body)*))
+    };
+    (repeater ( $count:expr ) { $($body:tt)* }) => {
+        $crate::helpers::repeater($crate::tree!(@single repeater $($body)*), $count)
+    };
+    (action ( $update:expr )) => {
+        $crate::helpers::action($update)
+    };
+    (cond ( $check:expr )) => {
+        $crate::helpers::condition($check)
+    };
+    (custom ( $behavior:expr )) => {
+        $crate::helpers::custom($behavior)
+    };
+    ($kind:ident { $($body:tt)* }) => {
+        compile_error!(concat!("unknown composite `", stringify!($kind), "`"))
+    };
+    ($builder:ident ( $($args:tt)* )) => {
+        $builder($($args)*)
+    };
+    ($leaf:ident) => {
+        $leaf
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        tracing::debug!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {};
+}
@@ -1,15 +1,25 @@
 pub mod action;
+pub mod async_action;
+pub mod blackboard_condition;
 //pub mod active_selector;
 pub mod decorator;
 //pub mod monitor;
 //pub mod parallel;
 pub mod selector;
 pub mod sequence;
+pub mod shared;
+pub mod subtree;
+pub mod wait;
 
 pub use action::*;
+pub use async_action::*;
+pub use blackboard_condition::*;
 //pub use active_selector::*;
 pub use decorator::*;
 //pub use monitor::*;
 //pub use parallel::*;
 pub use selector::*;
 pub use sequence::*;
+pub use shared::*;
+pub use subtree::*;
+pub use wait::*;
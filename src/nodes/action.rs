@@ -1,19 +1,28 @@
-use crate::{Behavior, FnOnComplete, Node, Status};
-use std::collections::VecDeque;
+use crate::persist::{NodeState, Persist};
+use crate::registry::ParamError;
+use crate::sync::MaybeSend;
+use crate::{Behavior, Context, Status};
+
+#[cfg(not(feature = "send"))]
+pub type Update = Box<dyn FnMut() -> Status>;
+
+#[cfg(feature = "send")]
+pub type Update = Box<dyn FnMut() -> Status + Send>;
+
 pub struct Action {
+    pub name: Option<String>,
     pub status: Status,
-    pub update: Box<dyn FnMut() -> Status>,
-    pub on_complete_cb: FnOnComplete,
+    pub update: Update,
 }
 
 impl Action {
-    pub fn new<T>(update: Box<T>, on_complete_cb: FnOnComplete) -> Self
+    pub fn new<T>(update: Box<T>) -> Self
     where
-        T: FnMut() -> Status + Copy + 'static,
+        T: FnMut() -> Status + Copy + MaybeSend + 'static,
     {
         Self {
+            name: None,
             update,
-            on_complete_cb,
             status: Status::Invalid,
         }
     }
@@ -24,14 +33,43 @@ impl Behavior for Action {
         self.status
     }
 
-    fn update(&mut self, _: &mut VecDeque<Node>) -> Status {
+    fn update(&mut self, _: &mut Context) -> Status {
         self.status = (self.update)();
-        self.status.clone()
+        self.status
     }
 
-    fn on_complete(&mut self, result: Status, events: &mut VecDeque<Node>) {
-        if let Some(cb) = &mut self.on_complete_cb {
-            cb(result, events)
-        }
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn kind(&self) -> &'static str {
+        "Action"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+}
+
+impl Persist for Action {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status)
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.status = state.status;
+        Ok(())
     }
 }
@@ -0,0 +1,114 @@
+use crate::sync::MaybeSend;
+use crate::{Behavior, Context, Status};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{self, Poll};
+
+#[cfg(not(feature = "send"))]
+pub type BoxFuture = Pin<Box<dyn Future<Output = Status>>>;
+
+#[cfg(feature = "send")]
+pub type BoxFuture = Pin<Box<dyn Future<Output = Status> + Send>>;
+
+#[cfg(not(feature = "send"))]
+pub type Start = Box<dyn FnMut() -> BoxFuture>;
+
+#[cfg(feature = "send")]
+pub type Start = Box<dyn FnMut() -> BoxFuture + Send>;
+
+pub trait IntoStatus {
+    fn into_status(self) -> Status;
+}
+
+impl IntoStatus for Status {
+    fn into_status(self) -> Status {
+        self
+    }
+}
+
+impl IntoStatus for bool {
+    fn into_status(self) -> Status {
+        if self {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+pub struct AsyncAction {
+    pub name: Option<String>,
+    pub status: Status,
+    pub start: Start,
+    future: Option<BoxFuture>,
+}
+
+impl AsyncAction {
+    pub fn new<F, Fut>(mut start: F) -> Self
+    where
+        F: FnMut() -> Fut + MaybeSend + 'static,
+        Fut: Future + MaybeSend + 'static,
+        Fut::Output: IntoStatus,
+    {
+        Self {
+            name: None,
+            status: Status::Invalid,
+            start: Box::new(move || {
+                let future = start();
+                Box::pin(async move { future.await.into_status() })
+            }),
+            future: None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.future.is_some()
+    }
+}
+
+impl Behavior for AsyncAction {
+    fn initialize(&mut self, _: &mut Context) {
+        self.future = Some((self.start)());
+        self.status = Status::Running;
+    }
+
+    fn update(&mut self, context: &mut Context) -> Status {
+        if let Some(future) = &mut self.future {
+            let waker = context.waker();
+            if let Poll::Ready(status) =
+                future.as_mut().poll(&mut task::Context::from_waker(&waker))
+            {
+                self.future = None;
+                self.status = status;
+            }
+        }
+        self.status
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    // Dropping the future is how it gets cancelled.
+    fn abort(&mut self) -> Status {
+        self.future = None;
+        self.status = Status::Aborted;
+        self.status
+    }
+
+    fn kind(&self) -> &'static str {
+        "AsyncAction"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::blackboard::{AbortMode, Observe};
+use crate::persist::{NodeState, Persist};
+use crate::registry::{Param, ParamError};
+use crate::sync::MaybeSend;
+use crate::validate::Problem;
+use crate::{Behavior, Context, Status};
+
+#[cfg(not(feature = "send"))]
+pub type Check = Box<dyn Fn(Option<&Param>) -> bool>;
+
+#[cfg(feature = "send")]
+pub type Check = Box<dyn Fn(Option<&Param>) -> bool + Send>;
+
+pub struct BlackboardCondition {
+    pub name: Option<String>,
+    pub key: String,
+    pub abort_mode: AbortMode,
+    pub check: Check,
+    pub status: Status,
+}
+
+impl BlackboardCondition {
+    pub fn new<F>(key: &str, abort_mode: AbortMode, check: F) -> Self
+    where
+        F: Fn(Option<&Param>) -> bool + MaybeSend + 'static,
+    {
+        Self {
+            name: None,
+            key: key.to_string(),
+            abort_mode,
+            check: Box::new(check),
+            status: Status::Invalid,
+        }
+    }
+}
+
+impl Behavior for BlackboardCondition {
+    fn initialize(&mut self, context: &mut Context) {
+        if self.check(context.blackboard().get(&self.key)) && context.start_child(0) {
+            self.status = Status::Running;
+        } else {
+            self.status = Status::Failure;
+        }
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn child_complete(&mut self, _: usize, result: Status, _: &mut Context) -> Status {
+        result
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn abort(&mut self) -> Status {
+        self.status = Status::Aborted;
+        self.status
+    }
+
+    fn kind(&self) -> &'static str {
+        "BlackboardCondition"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+
+    fn diagnose(&self, children: usize) -> Vec<Problem> {
+        if children != 1 {
+            vec![Problem::ChildCount {
+                expected: 1,
+                found: children,
+            }]
+        } else {
+            vec![]
+        }
+    }
+
+    fn observe(&self) -> Option<&dyn Observe> {
+        Some(self)
+    }
+}
+
+impl Observe for BlackboardCondition {
+    fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn abort_mode(&self) -> AbortMode {
+        self.abort_mode
+    }
+
+    fn check(&self, value: Option<&Param>) -> bool {
+        (self.check)(value)
+    }
+}
+
+impl Persist for BlackboardCondition {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status)
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.status = state.status;
+        Ok(())
+    }
+}
@@ -1,22 +1,24 @@
-use crate::{Behavior, FnOnComplete, Node, Status};
-use std::collections::VecDeque;
+use crate::persist::{NodeState, Persist};
+use crate::registry::{Param, ParamError};
+use crate::validate::Problem;
+use crate::{Behavior, Context, Status};
 
 pub struct Repeater {
-    pub node: Node,
+    pub name: Option<String>,
     pub repeat_for: i32,
     pub current_loop: i32,
+    pub restart: bool,
     pub status: Status,
-    pub on_complete_cb: FnOnComplete,
 }
 
 impl Repeater {
-    pub fn new(node: Node, repeat_for: i32, on_complete: FnOnComplete) -> Self {
+    pub fn new(repeat_for: i32) -> Self {
         Self {
-            node,
+            name: None,
             repeat_for,
             current_loop: 0,
+            restart: false,
             status: Status::Invalid,
-            on_complete_cb: on_complete,
         }
     }
 }
@@ -26,32 +28,92 @@ impl Behavior for Repeater {
         self.status
     }
 
-    fn initialize(&mut self, events: &mut VecDeque<Node>) {
-        self.node.borrow_mut().initialize(events);
-        self.status = Status::Running
+    fn initialize(&mut self, context: &mut Context) {
+        self.current_loop = 0;
+        self.restart = false;
+        self.status = Status::Running;
+        context.start_child(0);
     }
 
-    fn update(&mut self, events: &mut VecDeque<Node>) -> Status {
-        let mut node = self.node.borrow_mut();
-        let status = node.update(events);
-        if status == Status::Running {
+    fn update(&mut self, context: &mut Context) -> Status {
+        if self.restart {
+            self.restart = false;
+            context.start_child(0);
+        }
+        self.status
+    }
+
+    fn child_complete(&mut self, _: usize, result: Status, context: &mut Context) -> Status {
+        self.current_loop += 1;
+        trace_event!(
+            kind = "Repeater",
+            loop_count = self.current_loop,
+            repeat_for = self.repeat_for,
+            result = ?result,
+            "loop complete"
+        );
+        // Restart on the next pass rather than re-entering the child while it
+        // is still completing.
+        if self.current_loop < self.repeat_for {
+            self.restart = true;
+            context.schedule();
             Status::Running
         } else {
-            self.current_loop += 1;
-            if self.current_loop < self.repeat_for {
-                node.initialize(events);
-                Status::Running
-            } else {
-                self.status = status;
-                status
-            }
+            trace_event!(kind = "Repeater", from = ?self.status, to = ?result, "status changed");
+            result
         }
     }
 
-    fn on_complete(&mut self, result: Status, events: &mut VecDeque<Node>) {
-        self.status = result.clone();
-        if let Some(cb) = &mut self.on_complete_cb {
-            cb(result, events)
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn kind(&self) -> &'static str {
+        "Repeater"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn diagnose(&self, children: usize) -> Vec<Problem> {
+        let mut problems = vec![];
+        if children != 1 {
+            problems.push(Problem::ChildCount {
+                expected: 1,
+                found: children,
+            });
+        }
+        if self.repeat_for <= 0 {
+            problems.push(Problem::NoRepeats(self.repeat_for));
         }
+        problems
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+}
+
+impl Persist for Repeater {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status)
+            .with("current_loop", Param::Int(self.current_loop as i64))
+            .with("restart", Param::Bool(self.restart))
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.current_loop = state.values.get_int_as("current_loop")?;
+        self.restart = state.values.get_bool("restart")?;
+        self.status = state.status;
+        Ok(())
     }
 }
@@ -1,62 +1,112 @@
-use crate::{Behavior, FnOnComplete, Node, Status};
-use std::collections::VecDeque;
+use crate::persist::{NodeState, Persist};
+use crate::registry::{Param, ParamError};
+use crate::validate::Problem;
+use crate::{Behavior, Context, Status};
 
 pub struct Selector {
-    pub children: Vec<Node>,
+    pub name: Option<String>,
     pub current_child: i16,
     pub status: Status,
-    pub on_complete_cb: FnOnComplete,
 }
 
 impl Selector {
-    pub fn new(on_complete_cb: FnOnComplete) -> Self {
+    pub fn new() -> Self {
         Self {
-            children: vec![],
+            name: None,
             current_child: 0,
             status: Status::Invalid,
-            on_complete_cb,
         }
     }
 }
 
+impl Default for Selector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Behavior for Selector {
-    fn initialize(&mut self, events: &mut VecDeque<Node>) {
+    fn initialize(&mut self, context: &mut Context) {
         self.current_child = 0;
-        if let Some(child) = self.children.get(0) {
+        if context.start_child(0) {
             self.status = Status::Running;
-            events.push_back(child.clone());
-            child.borrow_mut().initialize(events);
         } else {
             self.status = Status::Failure
         }
     }
 
-    fn on_complete(&mut self, result: Status, events: &mut VecDeque<Node>) {
-        self.status = result.clone();
-        if let Some(cb) = &mut self.on_complete_cb {
-            cb(result, events)
-        }
+    fn on_complete(&mut self, result: Status) {
+        trace_event!(kind = "Selector", name = self.name(), from = ?self.status, to = ?result, "status changed");
+        self.status = result;
     }
 
-    fn child_complete(&mut self, result: Status, events: &mut VecDeque<Node>) {
+    fn child_complete(&mut self, _: usize, result: Status, context: &mut Context) -> Status {
+        trace_event!(
+            kind = "Selector",
+            name = self.name(),
+            child = self.current_child,
+            result = ?result,
+            "child complete"
+        );
         match result {
-            Status::Success => {
-                self.on_complete(result, events);
-            }
+            Status::Success => result,
             Status::Failure => {
                 self.current_child += 1;
-                if let Some(child) = self.children.get(self.current_child as usize) {
-                    events.push_back(child.clone());
-                    child.borrow_mut().initialize(events);
+                if context.start_child(self.current_child as usize) {
+                    Status::Running
                 } else {
-                    self.on_complete(result, events);
+                    result
                 }
             }
             _ => panic!("Invalid result: {:?}", &result),
-        };
+        }
     }
 
     fn status(&self) -> Status {
-        self.status.clone()
+        self.status
+    }
+
+    fn kind(&self) -> &'static str {
+        "Selector"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn is_selector(&self) -> bool {
+        true
+    }
+
+    fn diagnose(&self, children: usize) -> Vec<Problem> {
+        if children == 0 {
+            vec![Problem::EmptyComposite]
+        } else {
+            vec![]
+        }
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+}
+
+impl Persist for Selector {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status).with("current_child", Param::Int(self.current_child as i64))
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.current_child = state.values.get_int_as("current_child")?;
+        self.status = state.status;
+        Ok(())
     }
 }
@@ -0,0 +1,153 @@
+use crate::blackboard::{AbortMode, Observe};
+use crate::persist::{NodeState, Persist};
+use crate::registry::{Param, ParamError};
+use crate::sync::{MaybeSend, SharedCell};
+use crate::validate::Problem;
+use crate::{Behavior, Context, Status};
+
+// `P` is an `Rc<RefCell<_>>`, or an `Arc<Mutex<_>>` with the `send` feature.
+pub struct Shared<P> {
+    pub node: P,
+    // Names can't be borrowed out of the cell, so keep a copy alongside.
+    name: String,
+    // Likewise the observed key; `check` still goes through the cell.
+    observed: Option<(String, AbortMode)>,
+}
+
+impl<P> Shared<P>
+where
+    P: SharedCell,
+    P::Target: Behavior,
+{
+    pub fn new(node: P) -> Self {
+        let (name, observed) = node.read(|node| {
+            let observed = node
+                .observe()
+                .map(|observe| (observe.key().to_string(), observe.abort_mode()));
+            (node.name().to_string(), observed)
+        });
+        Self {
+            node,
+            name,
+            observed,
+        }
+    }
+}
+
+impl<P> Behavior for Shared<P>
+where
+    P: SharedCell + MaybeSend,
+    P::Target: Behavior,
+{
+    fn initialize(&mut self, context: &mut Context) {
+        self.node.write(|node| node.initialize(context))
+    }
+
+    fn update(&mut self, context: &mut Context) -> Status {
+        self.node.write(|node| node.update(context))
+    }
+
+    fn status(&self) -> Status {
+        self.node.read(|node| node.status())
+    }
+
+    fn child_complete(&mut self, child: usize, result: Status, context: &mut Context) -> Status {
+        self.node
+            .write(|node| node.child_complete(child, result, context))
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.node.write(|node| node.on_complete(result))
+    }
+
+    fn abort(&mut self) -> Status {
+        self.node.write(|node| node.abort())
+    }
+
+    fn kind(&self) -> &'static str {
+        self.node.read(|node| node.kind())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.node.write(|node| node.set_name(name));
+        self.name = name.to_string();
+    }
+
+    fn diagnose(&self, children: usize) -> Vec<Problem> {
+        self.node.read(|node| node.diagnose(children))
+    }
+
+    fn is_selector(&self) -> bool {
+        self.node.read(|node| node.is_selector())
+    }
+
+    fn instance(&self) -> Option<*const ()> {
+        Some(self.node.as_ptr())
+    }
+
+    fn observe(&self) -> Option<&dyn Observe> {
+        self.observed.as_ref().map(|_| self as &dyn Observe)
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        if self.node.read(|node| node.persist().is_some()) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        let persistent = self.node.write(|node| node.persist_mut().is_some());
+        if persistent {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<P> Observe for Shared<P>
+where
+    P: SharedCell + MaybeSend,
+    P::Target: Behavior,
+{
+    fn key(&self) -> &str {
+        self.observed.as_ref().map_or("", |(key, _)| key)
+    }
+
+    fn abort_mode(&self) -> AbortMode {
+        self.observed
+            .as_ref()
+            .map_or(AbortMode::None, |(_, mode)| *mode)
+    }
+
+    fn check(&self, value: Option<&Param>) -> bool {
+        self.node
+            .read(|node| node.observe().is_some_and(|observe| observe.check(value)))
+    }
+}
+
+impl<P> Persist for Shared<P>
+where
+    P: SharedCell + MaybeSend,
+    P::Target: Behavior,
+{
+    fn save(&self) -> NodeState {
+        self.node.read(|node| match node.persist() {
+            Some(persist) => persist.save(),
+            None => NodeState::new(node.status()),
+        })
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.node.write(|node| match node.persist_mut() {
+            Some(persist) => persist.load(state),
+            None => Ok(()),
+        })
+    }
+}
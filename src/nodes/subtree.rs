@@ -0,0 +1,86 @@
+use crate::persist::{NodeState, Persist};
+use crate::registry::ParamError;
+use crate::validate::Problem;
+use crate::{Behavior, Context, Status};
+
+// The blackboard scope itself is opened by the tree when the node is built.
+pub struct Subtree {
+    pub name: Option<String>,
+    pub status: Status,
+}
+
+impl Subtree {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            status: Status::Invalid,
+        }
+    }
+}
+
+impl Default for Subtree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Behavior for Subtree {
+    fn initialize(&mut self, context: &mut Context) {
+        if context.start_child(0) {
+            self.status = Status::Running;
+        } else {
+            self.status = Status::Failure;
+        }
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn child_complete(&mut self, _: usize, result: Status, _: &mut Context) -> Status {
+        result
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn kind(&self) -> &'static str {
+        "Subtree"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+
+    fn diagnose(&self, children: usize) -> Vec<Problem> {
+        match children {
+            0 => vec![Problem::UnresolvedSubtree(self.name().to_string())],
+            1 => vec![],
+            found => vec![Problem::ChildCount { expected: 1, found }],
+        }
+    }
+}
+
+impl Persist for Subtree {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status)
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.status = state.status;
+        Ok(())
+    }
+}
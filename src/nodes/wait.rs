@@ -0,0 +1,148 @@
+use crate::persist::{NodeState, Persist};
+use crate::registry::{Param, ParamError};
+use crate::{Behavior, Context, Status};
+
+pub struct Wait {
+    pub name: Option<String>,
+    pub ticks: u32,
+    pub remaining: u32,
+    pub status: Status,
+}
+
+impl Wait {
+    pub fn new(ticks: u32) -> Self {
+        Self {
+            name: None,
+            ticks,
+            remaining: ticks,
+            status: Status::Invalid,
+        }
+    }
+}
+
+impl Behavior for Wait {
+    fn initialize(&mut self, _: &mut Context) {
+        self.remaining = self.ticks;
+        self.status = Status::Running;
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn update(&mut self, _: &mut Context) -> Status {
+        if self.remaining == 0 {
+            self.status = Status::Success;
+        } else {
+            self.remaining -= 1;
+        }
+        self.status
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn kind(&self) -> &'static str {
+        "Wait"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+}
+
+impl Persist for Wait {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status).with("remaining", Param::Int(self.remaining as i64))
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.remaining = state.values.get_int_as("remaining")?;
+        self.status = state.status;
+        Ok(())
+    }
+}
+
+pub struct WaitFor {
+    pub name: Option<String>,
+    pub event: String,
+    pub status: Status,
+}
+
+impl WaitFor {
+    pub fn new(event: &str) -> Self {
+        Self {
+            name: None,
+            event: event.to_string(),
+            status: Status::Invalid,
+        }
+    }
+}
+
+impl Behavior for WaitFor {
+    fn initialize(&mut self, _: &mut Context) {
+        self.status = Status::Running;
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn update(&mut self, context: &mut Context) -> Status {
+        if context.notified() == Some(self.event.as_str()) {
+            self.status = Status::Success;
+        } else {
+            context.subscribe(&self.event);
+        }
+        self.status
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn kind(&self) -> &'static str {
+        "WaitFor"
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    fn persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
+    fn persist_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(self)
+    }
+}
+
+// The subscription itself is part of the tree's snapshot.
+impl Persist for WaitFor {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status)
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.status = state.status;
+        Ok(())
+    }
+}
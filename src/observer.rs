@@ -0,0 +1,178 @@
+use crate::sync::{MaybeSend, SharedCell};
+use crate::{NodeId, Status, Tree};
+#[cfg(not(feature = "send"))]
+use std::cell::RefCell;
+#[cfg(not(feature = "send"))]
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TraceEvent {
+    Initialize {
+        node: NodeId,
+    },
+    Update {
+        node: NodeId,
+        status: Status,
+        elapsed: Duration,
+    },
+    ChildComplete {
+        parent: NodeId,
+        child: NodeId,
+        status: Status,
+    },
+    Abort {
+        node: NodeId,
+    },
+    StatusChange {
+        node: NodeId,
+        from: Status,
+        to: Status,
+    },
+}
+
+impl TraceEvent {
+    pub fn node(&self) -> NodeId {
+        match *self {
+            TraceEvent::Initialize { node }
+            | TraceEvent::Update { node, .. }
+            | TraceEvent::Abort { node }
+            | TraceEvent::StatusChange { node, .. } => node,
+            TraceEvent::ChildComplete { parent, .. } => parent,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TraceRecord {
+    pub step: usize,
+    pub time: Instant,
+    pub event: TraceEvent,
+}
+
+pub trait TreeObserver: MaybeSend {
+    fn attach(&mut self, _: &Tree) {}
+
+    fn notify(&mut self, record: &TraceRecord);
+}
+
+#[cfg(not(feature = "send"))]
+impl<T: TreeObserver + 'static> TreeObserver for Rc<RefCell<T>> {
+    fn attach(&mut self, tree: &Tree) {
+        self.write(|observer| observer.attach(tree))
+    }
+
+    fn notify(&mut self, record: &TraceRecord) {
+        self.write(|observer| observer.notify(record))
+    }
+}
+
+impl<T: TreeObserver + 'static> TreeObserver for Arc<Mutex<T>> {
+    fn attach(&mut self, tree: &Tree) {
+        self.write(|observer| observer.attach(tree))
+    }
+
+    fn notify(&mut self, record: &TraceRecord) {
+        self.write(|observer| observer.notify(record))
+    }
+}
+
+#[derive(Default)]
+pub struct Recorder {
+    pub records: Vec<TraceRecord>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.records.iter().map(|record| record.event).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+impl TreeObserver for Recorder {
+    fn notify(&mut self, record: &TraceRecord) {
+        self.records.push(*record);
+    }
+}
+
+pub struct StdoutLogger {
+    start: Instant,
+    labels: Vec<String>,
+}
+
+impl StdoutLogger {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            labels: vec![],
+        }
+    }
+
+    fn label(&self, node: NodeId) -> String {
+        match self.labels.get(node.0) {
+            Some(label) => format!("{} {}", node, label),
+            None => node.to_string(),
+        }
+    }
+}
+
+impl Default for StdoutLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreeObserver for StdoutLogger {
+    fn attach(&mut self, tree: &Tree) {
+        self.labels.clear();
+        tree.walk(|visit| {
+            let node = visit.node;
+            self.labels.push(format!("[{}]", node.name()));
+        });
+    }
+
+    fn notify(&mut self, record: &TraceRecord) {
+        let elapsed = record.time.saturating_duration_since(self.start);
+        let message = match record.event {
+            TraceEvent::Initialize { node } => format!("{} initialize", self.label(node)),
+            TraceEvent::Update {
+                node,
+                status,
+                elapsed,
+            } => format!(
+                "{} update -> {:?} in {:?}",
+                self.label(node),
+                status,
+                elapsed
+            ),
+            TraceEvent::ChildComplete {
+                parent,
+                child,
+                status,
+            } => format!(
+                "{} child {} completed with {:?}",
+                self.label(parent),
+                self.label(child),
+                status
+            ),
+            TraceEvent::Abort { node } => format!("{} aborted", self.label(node)),
+            TraceEvent::StatusChange { node, from, to } => {
+                format!("{} {:?} -> {:?}", self.label(node), from, to)
+            }
+        };
+        println!(
+            "[{:>10.6}s] step {:>4} {}",
+            elapsed.as_secs_f64(),
+            record.step,
+            message
+        );
+    }
+}
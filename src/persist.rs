@@ -0,0 +1,244 @@
+use crate::registry::{Param, ParamError, Params};
+use crate::{NodeId, Status, Tree};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Debug)]
+pub struct NodeState {
+    pub status: Status,
+    pub values: Params,
+}
+
+impl NodeState {
+    pub fn new(status: Status) -> Self {
+        Self {
+            status,
+            values: Params::new(),
+        }
+    }
+
+    pub fn with(mut self, name: &str, value: Param) -> Self {
+        self.values.insert(name, value);
+        self
+    }
+}
+
+pub trait Persist {
+    fn save(&self) -> NodeState;
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError>;
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Debug)]
+pub struct NodeSnapshot {
+    pub kind: String,
+    pub state: Option<NodeState>,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct TreeSnapshot {
+    pub steps: usize,
+    pub nodes: Vec<NodeSnapshot>,
+    pub queue: Vec<NodeId>,
+    // One entry per blackboard scope, the tree's own scope first.
+    pub blackboard: Vec<Params>,
+    // Nodes parked on an event, and the event each one subscribed to.
+    pub waiting: Vec<(NodeId, String)>,
+    // Events delivered by `notify` that the woken node hasn't read yet.
+    pub notified: Vec<(NodeId, String)>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum RestoreError {
+    NodeCount {
+        expected: usize,
+        found: usize,
+    },
+    ScopeCount {
+        expected: usize,
+        found: usize,
+    },
+    KindMismatch {
+        node: NodeId,
+        expected: String,
+        found: String,
+    },
+    NotPersistent(NodeId),
+    UnknownNode(NodeId),
+    State {
+        node: NodeId,
+        error: ParamError,
+    },
+    // A node couldn't load the state it saved before the failed restore, so
+    // the tree is left partly restored.
+    Rollback {
+        node: NodeId,
+        error: ParamError,
+    },
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RestoreError::NodeCount { expected, found } => write!(
+                f,
+                "snapshot has {} nodes but the tree has {}",
+                found, expected
+            ),
+            RestoreError::ScopeCount { expected, found } => write!(
+                f,
+                "snapshot has {} blackboard scopes but the tree has {}",
+                found, expected
+            ),
+            RestoreError::KindMismatch {
+                node,
+                expected,
+                found,
+            } => write!(
+                f,
+                "node {} is a {} but the snapshot has a {}",
+                node, expected, found
+            ),
+            RestoreError::NotPersistent(node) => {
+                write!(f, "node {} does not support snapshots", node)
+            }
+            RestoreError::UnknownNode(node) => write!(f, "unknown node {} in snapshot", node),
+            RestoreError::State { node, error } => write!(f, "node {}: {}", node, error),
+            RestoreError::Rollback { node, error } => write!(
+                f,
+                "node {} could not be rolled back, the tree is partly restored: {}",
+                node, error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {}
+
+impl Tree {
+    pub fn snapshot(&self) -> TreeSnapshot {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| NodeSnapshot {
+                kind: node.kind().to_string(),
+                state: node.persist().map(|persist| persist.save()),
+            })
+            .collect();
+        let queue = self.pending();
+        // Subscribers keep their order so `notify` wakes them the same way.
+        let mut events: Vec<_> = self.subscriptions.iter().collect();
+        events.sort_by(|a, b| a.0.cmp(b.0));
+        let mut waiting = vec![];
+        for (event, subscribers) in events {
+            for id in subscribers.iter().filter(|id| self.waiting[id.0]) {
+                waiting.push((*id, event.clone()));
+            }
+        }
+        let notified = self
+            .notified
+            .iter()
+            .enumerate()
+            .filter_map(|(id, event)| event.clone().map(|event| (NodeId(id), event)))
+            .collect();
+        TreeSnapshot {
+            steps: self.steps,
+            nodes,
+            queue,
+            blackboard: self.blackboard.save(),
+            waiting,
+            notified,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &TreeSnapshot) -> Result<(), RestoreError> {
+        if snapshot.nodes.len() != self.nodes.len() {
+            return Err(RestoreError::NodeCount {
+                expected: self.nodes.len(),
+                found: snapshot.nodes.len(),
+            });
+        }
+        for (id, (node, saved)) in self.nodes.iter().zip(snapshot.nodes.iter()).enumerate() {
+            if node.kind() != saved.kind {
+                return Err(RestoreError::KindMismatch {
+                    node: NodeId(id),
+                    expected: node.kind().to_string(),
+                    found: saved.kind.clone(),
+                });
+            }
+            if saved.state.is_none() || node.persist().is_none() {
+                return Err(RestoreError::NotPersistent(NodeId(id)));
+            }
+        }
+        let parked = snapshot.waiting.iter().chain(&snapshot.notified);
+        if let Some(id) = snapshot
+            .queue
+            .iter()
+            .chain(parked.map(|(id, _)| id))
+            .find(|id| id.0 >= self.nodes.len())
+        {
+            return Err(RestoreError::UnknownNode(*id));
+        }
+        if snapshot.blackboard.len() != self.blackboard.scope_count() {
+            return Err(RestoreError::ScopeCount {
+                expected: self.blackboard.scope_count(),
+                found: snapshot.blackboard.len(),
+            });
+        }
+
+        // A state that fails to load puts back the nodes loaded before it, so
+        // a failed restore leaves the tree as it was.
+        let previous: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| node.persist().map(Persist::save))
+            .collect();
+        for (id, saved) in snapshot.nodes.iter().enumerate() {
+            let loaded = match (self.nodes[id].persist_mut(), &saved.state) {
+                (Some(persist), Some(state)) => persist.load(state),
+                _ => Ok(()),
+            };
+            if let Err(error) = loaded {
+                for (index, state) in previous.iter().enumerate().take(id + 1) {
+                    if let (Some(persist), Some(state)) = (self.nodes[index].persist_mut(), state) {
+                        persist
+                            .load(state)
+                            .map_err(|error| RestoreError::Rollback {
+                                node: NodeId(index),
+                                error,
+                            })?;
+                    }
+                }
+                return Err(RestoreError::State {
+                    node: NodeId(id),
+                    error,
+                });
+            }
+        }
+        self.events = snapshot.queue.iter().copied().collect();
+        self.started.iter_mut().for_each(|started| *started = false);
+        self.steps = snapshot.steps;
+        self.blackboard.load(&snapshot.blackboard);
+        self.observe_all();
+        self.subscriptions.clear();
+        self.waiting.iter_mut().for_each(|waiting| *waiting = false);
+        for (id, event) in &snapshot.waiting {
+            self.waiting[id.0] = true;
+            self.subscriptions
+                .entry(event.clone())
+                .or_default()
+                .push(*id);
+        }
+        self.notified
+            .iter_mut()
+            .for_each(|notified| *notified = None);
+        for (id, event) in &snapshot.notified {
+            self.notified[id.0] = Some(event.clone());
+        }
+        Ok(())
+    }
+}
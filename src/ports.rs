@@ -0,0 +1,254 @@
+use crate::registry::Param;
+use crate::NodeId;
+use std::fmt;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+pub trait PortType: Sized {
+    const TYPE_NAME: &'static str;
+
+    fn from_param(param: &Param) -> Option<Self>;
+
+    fn into_param(self) -> Param;
+
+    fn parse(value: &str) -> Option<Param> {
+        Some(Param::Str(value.to_string()))
+    }
+}
+
+impl PortType for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn into_param(self) -> Param {
+        Param::Bool(self)
+    }
+
+    fn parse(value: &str) -> Option<Param> {
+        value.parse().ok().map(Param::Bool)
+    }
+}
+
+impl PortType for i64 {
+    const TYPE_NAME: &'static str = "int";
+
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn into_param(self) -> Param {
+        Param::Int(self)
+    }
+
+    fn parse(value: &str) -> Option<Param> {
+        value.parse().ok().map(Param::Int)
+    }
+}
+
+impl PortType for f64 {
+    const TYPE_NAME: &'static str = "float";
+
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn into_param(self) -> Param {
+        Param::Float(self)
+    }
+
+    fn parse(value: &str) -> Option<Param> {
+        value.parse().ok().map(Param::Float)
+    }
+}
+
+impl PortType for String {
+    const TYPE_NAME: &'static str = "string";
+
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Str(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn into_param(self) -> Param {
+        Param::Str(self)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PortSpec {
+    pub name: String,
+    pub direction: Direction,
+    pub type_name: &'static str,
+    pub default: Option<Param>,
+    parse: fn(&str) -> Option<Param>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Ports {
+    specs: Vec<PortSpec>,
+}
+
+impl Ports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn input<T: PortType>(self, name: &str) -> Self {
+        self.with::<T>(name, Direction::Input, None)
+    }
+
+    // Inputs with a default don't have to be bound.
+    pub fn input_or<T: PortType>(self, name: &str, default: T) -> Self {
+        self.with::<T>(name, Direction::Input, Some(default.into_param()))
+    }
+
+    pub fn output<T: PortType>(self, name: &str) -> Self {
+        self.with::<T>(name, Direction::Output, None)
+    }
+
+    fn with<T: PortType>(
+        mut self,
+        name: &str,
+        direction: Direction,
+        default: Option<Param>,
+    ) -> Self {
+        self.specs.retain(|spec| spec.name != name);
+        self.specs.push(PortSpec {
+            name: name.to_string(),
+            direction,
+            type_name: T::TYPE_NAME,
+            default,
+            parse: T::parse,
+        });
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PortSpec> {
+        self.specs.iter().find(|spec| spec.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PortSpec> {
+        self.specs.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Binding {
+    Key(String),
+    Value(Param),
+}
+
+#[derive(Clone, Debug)]
+pub struct BoundPort {
+    pub spec: PortSpec,
+    pub binding: Option<Binding>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum PortError {
+    UnknownPort {
+        node: NodeId,
+        port: String,
+    },
+    InvalidValue {
+        node: NodeId,
+        port: String,
+        expected: &'static str,
+        value: String,
+    },
+    OutputNotKey {
+        node: NodeId,
+        port: String,
+    },
+}
+
+impl fmt::Display for PortError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PortError::UnknownPort { node, port } => {
+                write!(f, "node {} has no port `{}`", node, port)
+            }
+            PortError::InvalidValue {
+                node,
+                port,
+                expected,
+                value,
+            } => write!(
+                f,
+                "port `{}` of node {} expects {} but got `{}`",
+                port, node, expected, value
+            ),
+            PortError::OutputNotKey { node, port } => write!(
+                f,
+                "output port `{}` of node {} must map to a `{{key}}`",
+                port, node
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PortError {}
+
+// `{key}` maps a port to a blackboard key; anything else is a constant.
+pub(crate) fn bind(
+    node: NodeId,
+    ports: &Ports,
+    remap: Vec<(String, String)>,
+) -> Result<Vec<BoundPort>, PortError> {
+    let mut bound: Vec<BoundPort> = ports
+        .iter()
+        .map(|spec| BoundPort {
+            spec: spec.clone(),
+            binding: spec.default.clone().map(Binding::Value),
+        })
+        .collect();
+    for (port, value) in remap {
+        let slot = match bound.iter_mut().find(|bound| bound.spec.name == port) {
+            Some(slot) => slot,
+            None => return Err(PortError::UnknownPort { node, port }),
+        };
+        let key = value
+            .strip_prefix('{')
+            .and_then(|value| value.strip_suffix('}'));
+        slot.binding = Some(match key {
+            Some(key) => Binding::Key(key.to_string()),
+            None if slot.spec.direction == Direction::Output => {
+                return Err(PortError::OutputNotKey { node, port })
+            }
+            None => match (slot.spec.parse)(&value) {
+                Some(param) => Binding::Value(param),
+                None => {
+                    return Err(PortError::InvalidValue {
+                        node,
+                        port,
+                        expected: slot.spec.type_name,
+                        value,
+                    })
+                }
+            },
+        });
+    }
+    Ok(bound)
+}
@@ -0,0 +1,183 @@
+use crate::observer::{TraceEvent, TraceRecord, TreeObserver};
+use crate::{NodeId, Status, Tree};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct NodeProfile {
+    pub id: NodeId,
+    pub kind: String,
+    pub name: String,
+    pub ticks: usize,
+    pub total_time: Duration,
+    pub max_time: Duration,
+    pub successes: usize,
+    pub failures: usize,
+    pub aborts: usize,
+    pub completed_ticks: usize,
+    leaf: bool,
+    // Updates since the last Initialize, while the node is running.
+    running_ticks: Option<usize>,
+}
+
+impl NodeProfile {
+    pub fn completions(&self) -> usize {
+        self.successes + self.failures
+    }
+
+    fn complete(&mut self, status: Status) {
+        match status {
+            Status::Success => self.successes += 1,
+            Status::Failure => self.failures += 1,
+            _ => return,
+        }
+        if let Some(ticks) = self.running_ticks.take() {
+            self.completed_ticks += ticks;
+        }
+    }
+
+    pub fn average_time(&self) -> Duration {
+        if self.ticks == 0 {
+            Duration::default()
+        } else {
+            self.total_time.div_f64(self.ticks as f64)
+        }
+    }
+
+    pub fn average_ticks_to_completion(&self) -> Option<f64> {
+        match self.completions() {
+            0 => None,
+            completions => Some(self.completed_ticks as f64 / completions as f64),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Profiler {
+    nodes: Vec<NodeProfile>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&self) -> ProfileReport {
+        ProfileReport {
+            nodes: self.nodes.clone(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for node in self.nodes.iter_mut() {
+            *node = NodeProfile {
+                id: node.id,
+                kind: node.kind.clone(),
+                name: node.name.clone(),
+                leaf: node.leaf,
+                ..NodeProfile::default()
+            };
+        }
+    }
+}
+
+impl TreeObserver for Profiler {
+    fn attach(&mut self, tree: &Tree) {
+        self.nodes.clear();
+        tree.walk(|visit| {
+            let node = visit.node;
+            self.nodes.push(NodeProfile {
+                id: visit.id,
+                kind: node.kind().to_string(),
+                name: node.name().to_string(),
+                leaf: tree.children(visit.id).is_empty(),
+                ..NodeProfile::default()
+            });
+        });
+    }
+
+    fn notify(&mut self, record: &TraceRecord) {
+        let profile = match self.nodes.get_mut(record.event.node().0) {
+            Some(profile) => profile,
+            None => return,
+        };
+        // Leaves report their result through update, composites only through
+        // the status they are left in by their children.
+        match record.event {
+            TraceEvent::Initialize { .. } => profile.running_ticks = Some(0),
+            TraceEvent::Update {
+                status, elapsed, ..
+            } => {
+                profile.ticks += 1;
+                profile.total_time += elapsed;
+                profile.max_time = profile.max_time.max(elapsed);
+                if let Some(ticks) = &mut profile.running_ticks {
+                    *ticks += 1;
+                }
+                if profile.leaf {
+                    profile.complete(status);
+                }
+            }
+            TraceEvent::StatusChange {
+                to: Status::Aborted,
+                ..
+            } => {
+                profile.aborts += 1;
+                profile.running_ticks = None;
+            }
+            TraceEvent::StatusChange { to, .. } if !profile.leaf => profile.complete(to),
+            _ => {}
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ProfileReport {
+    pub nodes: Vec<NodeProfile>,
+}
+
+impl ProfileReport {
+    pub fn node(&self, id: NodeId) -> Option<&NodeProfile> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.nodes.iter().map(|node| node.total_time).sum()
+    }
+
+    pub fn by_total_time(&self) -> Vec<&NodeProfile> {
+        let mut nodes: Vec<&NodeProfile> = self.nodes.iter().collect();
+        nodes.sort_by_key(|node| std::cmp::Reverse(node.total_time));
+        nodes
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<6} {:<24} {:>7} {:>12} {:>12} {:>6} {:>6} {:>6} {:>10}",
+            "node", "name", "ticks", "total", "max", "succ", "fail", "abort", "avg ticks"
+        )?;
+        for node in self.nodes.iter() {
+            let average = match node.average_ticks_to_completion() {
+                Some(average) => format!("{:.2}", average),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:<6} {:<24} {:>7} {:>12} {:>12} {:>6} {:>6} {:>6} {:>10}",
+                node.id.to_string(),
+                format!("{} ({})", node.name, node.kind),
+                node.ticks,
+                format!("{:?}", node.total_time),
+                format!("{:?}", node.max_time),
+                node.successes,
+                node.failures,
+                node.aborts,
+                average
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,557 @@
+use crate::blackboard::Scope;
+use crate::helpers::*;
+use crate::sync;
+use crate::{CustomBehavior, NodeBuilder, Status};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+type Factory = Box<dyn Fn(&Params, Vec<NodeBuilder>) -> Result<NodeBuilder, RegistryError>>;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Debug)]
+pub enum Param {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl Param {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Param::Bool(_) => "bool",
+            Param::Int(_) => "int",
+            Param::Float(_) => "float",
+            Param::Str(_) => "string",
+        }
+    }
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Param::Bool(value) => write!(f, "{}", value),
+            Param::Int(value) => write!(f, "{}", value),
+            Param::Float(value) => write!(f, "{}", value),
+            Param::Str(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Params {
+    values: HashMap<String, Param>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: &str, value: Param) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: &str, value: Param) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Param, ParamError> {
+        self.values
+            .get(name)
+            .ok_or_else(|| ParamError::Missing(name.to_string()))
+    }
+
+    pub fn get_bool(&self, name: &str) -> Result<bool, ParamError> {
+        match self.get(name)? {
+            Param::Bool(value) => Ok(*value),
+            other => Err(ParamError::wrong_type(name, "bool", other)),
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Result<i64, ParamError> {
+        match self.get(name)? {
+            Param::Int(value) => Ok(*value),
+            other => Err(ParamError::wrong_type(name, "int", other)),
+        }
+    }
+
+    // For ints stored in a narrower type, e.g. `let ticks: u32 = params.get_int_as("ticks")?`.
+    pub fn get_int_as<T: TryFrom<i64>>(&self, name: &str) -> Result<T, ParamError> {
+        let value = self.get_int(name)?;
+        T::try_from(value).map_err(|_| ParamError::OutOfRange {
+            name: name.to_string(),
+            value,
+        })
+    }
+
+    pub fn get_float(&self, name: &str) -> Result<f64, ParamError> {
+        match self.get(name)? {
+            Param::Float(value) => Ok(*value),
+            Param::Int(value) => Ok(*value as f64),
+            other => Err(ParamError::wrong_type(name, "float", other)),
+        }
+    }
+
+    pub fn get_str(&self, name: &str) -> Result<&str, ParamError> {
+        match self.get(name)? {
+            Param::Str(value) => Ok(value),
+            other => Err(ParamError::wrong_type(name, "string", other)),
+        }
+    }
+
+    pub fn get_status(&self, name: &str) -> Result<Status, ParamError> {
+        match self.get_str(name)? {
+            "success" => Ok(Status::Success),
+            "failure" => Ok(Status::Failure),
+            "running" => Ok(Status::Running),
+            _ => Err(ParamError::WrongType {
+                name: name.to_string(),
+                expected: "status",
+                found: "string",
+            }),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Param)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ParamError {
+    Missing(String),
+    WrongType {
+        name: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    OutOfRange {
+        name: String,
+        value: i64,
+    },
+}
+
+impl ParamError {
+    fn wrong_type(name: &str, expected: &'static str, found: &Param) -> Self {
+        ParamError::WrongType {
+            name: name.to_string(),
+            expected,
+            found: found.type_name(),
+        }
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamError::Missing(name) => write!(f, "missing parameter `{}`", name),
+            ParamError::WrongType {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "parameter `{}` should be {} but is {}",
+                name, expected, found
+            ),
+            ParamError::OutOfRange { name, value } => {
+                write!(f, "parameter `{}` is out of range: {}", name, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum RegistryError {
+    UnknownNode(String),
+    UnknownSubtree(String),
+    RecursiveSubtree(String),
+    Parameter {
+        node: String,
+        error: ParamError,
+    },
+    ChildCount {
+        node: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownNode(id) => write!(f, "unknown node type `{}`", id),
+            RegistryError::UnknownSubtree(id) => write!(f, "unknown subtree `{}`", id),
+            RegistryError::RecursiveSubtree(id) => write!(f, "subtree `{}` contains itself", id),
+            RegistryError::Parameter { node, error } => write!(f, "node `{}`: {}", node, error),
+            RegistryError::ChildCount {
+                node,
+                expected,
+                found,
+            } => write!(
+                f,
+                "node `{}` expects {} children but got {}",
+                node, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct NodeSpec {
+    pub id: String,
+    pub name: Option<String>,
+    pub params: Params,
+    pub children: Vec<NodeSpec>,
+}
+
+impl NodeSpec {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: None,
+            params: Params::new(),
+            children: vec![],
+        }
+    }
+
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn param(mut self, name: &str, value: Param) -> Self {
+        self.params.insert(name, value);
+        self
+    }
+
+    pub fn child(mut self, child: NodeSpec) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn parse(source: &str) -> Result<NodeSpec, SpecParseError> {
+        let mut root = None;
+        let mut stack: Vec<(usize, NodeSpec)> = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let content = line.split('#').next().unwrap_or("");
+            if content.trim().is_empty() {
+                continue;
+            }
+            if content.starts_with('\t') {
+                return Err(SpecParseError::new(line_number, "tabs are not allowed"));
+            }
+            let indent = content.len() - content.trim_start().len();
+            while stack.last().is_some_and(|(top, _)| *top >= indent) {
+                let (_, done) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.children.push(done),
+                    None => root = Some(done),
+                }
+            }
+            if stack.is_empty() && root.is_some() {
+                return Err(SpecParseError::new(line_number, "only one root node"));
+            }
+            stack.push((indent, parse_line(content.trim(), line_number)?));
+        }
+        while let Some((_, done)) = stack.pop() {
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(done),
+                None => root = Some(done),
+            }
+        }
+        root.ok_or_else(|| SpecParseError::new(0, "no nodes"))
+    }
+}
+
+fn parse_line(line: &str, line_number: usize) -> Result<NodeSpec, SpecParseError> {
+    let mut words = line.split_whitespace();
+    let mut spec = NodeSpec::new(words.next().unwrap_or_default());
+    for word in words {
+        let (key, value) = match word.split_once('=') {
+            Some((key, value)) if !key.is_empty() && !value.is_empty() => (key, value),
+            _ => {
+                return Err(SpecParseError::new(
+                    line_number,
+                    &format!("expected `key=value` but found `{}`", word),
+                ))
+            }
+        };
+        let value = value.trim_matches('"');
+        if key == "name" {
+            spec = spec.named(value);
+        } else {
+            spec.params.insert(key, parse_value(value));
+        }
+    }
+    Ok(spec)
+}
+
+fn parse_value(value: &str) -> Param {
+    if let Ok(value) = value.parse() {
+        Param::Bool(value)
+    } else if let Ok(value) = value.parse() {
+        Param::Int(value)
+    } else if let Ok(value) = value.parse() {
+        Param::Float(value)
+    } else {
+        Param::Str(value.to_string())
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SpecParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl SpecParseError {
+    fn new(line: usize, message: &str) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SpecParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SpecParseError {}
+
+pub struct NodeRegistry {
+    factories: HashMap<String, Factory>,
+    subtrees: HashMap<String, NodeSpec>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register_builder("sequence", |_, children| Ok(sequence(children)));
+        registry.register_builder("selector", |_, children| Ok(selector(children)));
+        registry.register_builder("repeater", |params, mut children| {
+            let count = params.get_int_as::<i32>("count")?;
+            if children.len() != 1 {
+                return Err(FactoryError::Children(1));
+            }
+            Ok(repeater(children.remove(0), count))
+        });
+        registry.register_builder("wait", |params, children| {
+            if !children.is_empty() {
+                return Err(FactoryError::Children(0));
+            }
+            Ok(wait(params.get_int_as::<u32>("ticks")?))
+        });
+        registry.register_leaf("success", || action(|| Status::Success));
+        registry.register_leaf("failure", || action(|| Status::Failure));
+        registry
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+            subtrees: HashMap::new(),
+        }
+    }
+
+    pub fn register<B, F>(&mut self, id: &str, factory: F)
+    where
+        B: CustomBehavior + 'static,
+        F: Fn(&Params) -> Result<B, ParamError> + 'static,
+    {
+        self.register_builder(id, move |params, children| {
+            if !children.is_empty() {
+                return Err(FactoryError::Children(0));
+            }
+            Ok(custom(sync::handle(factory(params)?)))
+        });
+    }
+
+    fn register_leaf<F>(&mut self, id: &str, builder: F)
+    where
+        F: Fn() -> NodeBuilder + 'static,
+    {
+        self.register_builder(id, move |_, children| {
+            if !children.is_empty() {
+                return Err(FactoryError::Children(0));
+            }
+            Ok(builder())
+        });
+    }
+
+    fn register_builder<F>(&mut self, id: &str, factory: F)
+    where
+        F: Fn(&Params, Vec<NodeBuilder>) -> Result<NodeBuilder, FactoryError> + 'static,
+    {
+        let node = id.to_string();
+        self.factories.insert(
+            id.to_string(),
+            Box::new(move |params, children| {
+                let found = children.len();
+                factory(params, children).map_err(|error| match error {
+                    FactoryError::Param(error) => RegistryError::Parameter {
+                        node: node.clone(),
+                        error,
+                    },
+                    FactoryError::Children(expected) => RegistryError::ChildCount {
+                        node: node.clone(),
+                        expected,
+                        found,
+                    },
+                })
+            }),
+        );
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.factories.contains_key(id)
+    }
+
+    pub fn build(
+        &self,
+        id: &str,
+        params: &Params,
+        children: Vec<NodeBuilder>,
+    ) -> Result<NodeBuilder, RegistryError> {
+        match self.factories.get(id) {
+            Some(factory) => factory(params, children),
+            None => Err(RegistryError::UnknownNode(id.to_string())),
+        }
+    }
+
+    // Templates are instantiated from `subtree ref=<id>` nodes, each in its own
+    // blackboard scope.
+    pub fn register_subtree(&mut self, id: &str, template: NodeSpec) {
+        self.subtrees.insert(id.to_string(), template);
+    }
+
+    pub fn contains_subtree(&self, id: &str) -> bool {
+        self.subtrees.contains_key(id)
+    }
+
+    pub fn instantiate(&self, spec: &NodeSpec) -> Result<NodeBuilder, RegistryError> {
+        self.instantiate_in(spec, &mut vec![])
+    }
+
+    fn instantiate_in(
+        &self,
+        spec: &NodeSpec,
+        expanding: &mut Vec<String>,
+    ) -> Result<NodeBuilder, RegistryError> {
+        if spec.id == "subtree" {
+            return self.instantiate_subtree(spec, expanding);
+        }
+        if !self.contains(&spec.id) {
+            return Err(RegistryError::UnknownNode(spec.id.clone()));
+        }
+        let children = spec
+            .children
+            .iter()
+            .map(|child| self.instantiate_in(child, expanding))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut builder = self.build(&spec.id, &spec.params, children)?;
+        // Parameters named after a declared port bind that port.
+        let ports: Vec<String> = builder
+            .ports()
+            .iter()
+            .map(|port| port.name.clone())
+            .collect();
+        for port in ports {
+            if let Ok(value) = spec.params.get(&port) {
+                builder = builder.port(&port, &value.to_string());
+            }
+        }
+        Ok(match &spec.name {
+            Some(name) => named(name, builder),
+            None => builder,
+        })
+    }
+
+    // Other parameters remap keys inside the subtree: `target={enemy}`.
+    fn instantiate_subtree(
+        &self,
+        spec: &NodeSpec,
+        expanding: &mut Vec<String>,
+    ) -> Result<NodeBuilder, RegistryError> {
+        let reference = spec
+            .params
+            .get_str("ref")
+            .map_err(|error| RegistryError::Parameter {
+                node: spec.id.clone(),
+                error,
+            })?;
+        let template = self
+            .subtrees
+            .get(reference)
+            .ok_or_else(|| RegistryError::UnknownSubtree(reference.to_string()))?;
+        if expanding.iter().any(|id| id == reference) {
+            return Err(RegistryError::RecursiveSubtree(reference.to_string()));
+        }
+        if !spec.children.is_empty() {
+            return Err(RegistryError::ChildCount {
+                node: spec.id.clone(),
+                expected: 0,
+                found: spec.children.len(),
+            });
+        }
+        // Everything but `ref` and `inherit` remaps a key and must be a `{key}`.
+        let mut scope = Scope::new();
+        for (key, value) in spec.params.iter() {
+            let error = match (key, value) {
+                ("ref", _) | ("inherit", Param::Bool(false)) => continue,
+                ("inherit", Param::Bool(true)) => {
+                    scope = scope.inherit();
+                    continue;
+                }
+                ("inherit", other) => ParamError::wrong_type(key, "bool", other),
+                (key, Param::Str(outer)) if outer.starts_with('{') && outer.ends_with('}') => {
+                    scope = scope.remap(key, outer);
+                    continue;
+                }
+                (key, other) => ParamError::wrong_type(key, "a `{key}`", other),
+            };
+            return Err(RegistryError::Parameter {
+                node: spec.id.clone(),
+                error,
+            });
+        }
+        expanding.push(reference.to_string());
+        let child = self.instantiate_in(template, expanding);
+        expanding.pop();
+        let builder = subtree(scope, child?);
+        Ok(named(spec.name.as_deref().unwrap_or(reference), builder))
+    }
+}
+
+impl Default for NodeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum FactoryError {
+    Param(ParamError),
+    Children(usize),
+}
+
+impl From<ParamError> for FactoryError {
+    fn from(error: ParamError) -> Self {
+        FactoryError::Param(error)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::observer::{TraceEvent, TraceRecord, TreeObserver};
+use crate::{NodeId, Status, Tree};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct LogEntry {
+    pub tick: usize,
+    pub node: NodeId,
+    pub status: Status,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ExecutionLog {
+    pub transitions: Vec<LogEntry>,
+    pub results: Vec<LogEntry>,
+}
+
+impl ExecutionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn result(&self, tick: usize, node: NodeId) -> Option<Status> {
+        let start = self.results.partition_point(|entry| entry.tick < tick);
+        self.results[start..]
+            .iter()
+            .take_while(|entry| entry.tick == tick)
+            .find(|entry| entry.node == node)
+            .map(|entry| entry.status)
+    }
+
+    // The status `node` was left in by the last transition up to `tick`.
+    pub fn status(&self, tick: usize, node: NodeId) -> Option<Status> {
+        let end = self.transitions.partition_point(|entry| entry.tick <= tick);
+        self.transitions[..end]
+            .iter()
+            .rev()
+            .find(|entry| entry.node == node)
+            .map(|entry| entry.status)
+    }
+}
+
+#[derive(Default)]
+pub struct ExecutionRecorder {
+    pub log: ExecutionLog,
+    leaves: Vec<bool>,
+    start: usize,
+}
+
+impl ExecutionRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_log(self) -> ExecutionLog {
+        self.log
+    }
+}
+
+impl TreeObserver for ExecutionRecorder {
+    fn attach(&mut self, tree: &Tree) {
+        self.start = tree.steps();
+        self.leaves.clear();
+        tree.walk(|visit| self.leaves.push(tree.children(visit.id).is_empty()));
+    }
+
+    fn notify(&mut self, record: &TraceRecord) {
+        match record.event {
+            TraceEvent::StatusChange { node, to, .. } => self.log.transitions.push(LogEntry {
+                tick: record.step - self.start,
+                node,
+                status: to,
+            }),
+            TraceEvent::Update { node, status, .. } if self.leaves.get(node.0) == Some(&true) => {
+                self.log.results.push(LogEntry {
+                    tick: record.step - self.start,
+                    node,
+                    status,
+                })
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct Replay {
+    pub log: ExecutionLog,
+    pub divergence: Option<(usize, NodeId)>,
+    start: usize,
+}
+
+impl Replay {
+    pub(crate) fn new(log: ExecutionLog, start: usize) -> Self {
+        Self {
+            log,
+            divergence: None,
+            start,
+        }
+    }
+
+    pub(crate) fn result(&mut self, step: usize, node: NodeId) -> Status {
+        let tick = step - self.start;
+        match self.log.result(tick, node) {
+            Some(status) => status,
+            None => {
+                if self.divergence.is_none() {
+                    self.divergence = Some((tick, node));
+                }
+                Status::Failure
+            }
+        }
+    }
+
+    pub(crate) fn status(&self, step: usize, node: NodeId) -> Option<Status> {
+        self.log.status(step - self.start, node)
+    }
+}
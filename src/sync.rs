@@ -0,0 +1,87 @@
+//! Thread safety is opt-in through the `send` feature.
+//!
+//! By default trees are single-threaded: behaviors, observers and closures
+//! don't have to be `Send`, and shared nodes live in `Rc<RefCell<_>>`. With
+//! `send` enabled all of them must be `Send`, so a `Tree` can move to another
+//! thread, and shared nodes live in `Arc<Mutex<_>>` instead.
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[cfg(feature = "send")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "send")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+#[cfg(not(feature = "send"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "send"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+// The handle the tree uses for the observers it owns.
+#[cfg(not(feature = "send"))]
+pub type Handle<T> = Rc<RefCell<T>>;
+
+#[cfg(feature = "send")]
+pub type Handle<T> = Arc<Mutex<T>>;
+
+pub fn handle<T: 'static>(value: T) -> Handle<T> {
+    SharedCell::new(value)
+}
+
+// A value owned both by the tree and by the code that built it.
+pub trait SharedCell: 'static {
+    type Target;
+
+    fn new(value: Self::Target) -> Self;
+
+    fn read<R>(&self, f: impl FnOnce(&Self::Target) -> R) -> R;
+
+    fn write<R>(&self, f: impl FnOnce(&mut Self::Target) -> R) -> R;
+
+    fn as_ptr(&self) -> *const ();
+}
+
+impl<T: 'static> SharedCell for Rc<RefCell<T>> {
+    type Target = T;
+
+    fn new(value: T) -> Self {
+        Rc::new(RefCell::new(value))
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.borrow())
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+
+    fn as_ptr(&self) -> *const () {
+        Rc::as_ptr(self) as *const ()
+    }
+}
+
+// A panic while the lock is held doesn't leave the node in a state the tree
+// can't handle, so poisoning is ignored.
+impl<T: 'static> SharedCell for Arc<Mutex<T>> {
+    type Target = T;
+
+    fn new(value: T) -> Self {
+        Arc::new(Mutex::new(value))
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn as_ptr(&self) -> *const () {
+        Arc::as_ptr(self) as *const ()
+    }
+}
@@ -0,0 +1,42 @@
+use crate::NodeId;
+use std::fmt;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Problem {
+    EmptyComposite,
+    ChildCount { expected: usize, found: usize },
+    NoRepeats(i32),
+    DuplicateName(String),
+    UnresolvedSubtree(String),
+    UnboundPort(String),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Diagnostic {
+    pub node: NodeId,
+    pub problem: Problem,
+}
+
+impl Diagnostic {
+    // Duplicate names only make logs and lookups ambiguous; everything else
+    // changes how the tree runs.
+    pub fn is_error(&self) -> bool {
+        !matches!(self.problem, Problem::DuplicateName(_))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "node {}: ", self.node)?;
+        match &self.problem {
+            Problem::EmptyComposite => write!(f, "composite has no children"),
+            Problem::ChildCount { expected, found } => {
+                write!(f, "expects {} children but has {}", expected, found)
+            }
+            Problem::NoRepeats(count) => write!(f, "repeats {} times", count),
+            Problem::DuplicateName(name) => write!(f, "name `{}` is already used", name),
+            Problem::UnresolvedSubtree(name) => write!(f, "subtree `{}` has no body", name),
+            Problem::UnboundPort(port) => write!(f, "port `{}` is not bound", port),
+        }
+    }
+}
@@ -1,11 +1,13 @@
 extern crate behave;
 use behave::*;
-use std::collections::VecDeque;
 
+#[derive(Behavior)]
 pub struct CallCounterAction {
     pub call_count: i32,
+    #[behave(status)]
     status: Status,
-    on_complete_cb: FnOnComplete,
+    #[behave(name)]
+    name: Option<String>,
     result: Status,
 }
 
@@ -14,35 +16,15 @@ impl CallCounterAction {
         Self {
             call_count: 0,
             status: Status::Invalid,
-            on_complete_cb: None,
+            name: None,
             result,
         }
     }
 }
 
-impl Behavior for CallCounterAction {
-    fn initialize(&mut self, _: &mut VecDeque<Node>) {
-        self.status = Status::Running
-    }
-
-    fn status(&self) -> Status {
-        Status::Running
-    }
-
-    fn update(&mut self, _: &mut VecDeque<Node>) -> Status {
+impl Update for CallCounterAction {
+    fn update(&mut self, _: &mut Context) -> Status {
         self.call_count += 1;
-        self.status = self.result;
         self.result
     }
-
-    fn on_complete(&mut self, result: Status, events: &mut VecDeque<Node>) {
-        if let Some(cb) = &mut self.on_complete_cb {
-            cb(result, events)
-        }
-    }
-}
-impl CustomBehavior for CallCounterAction {
-    fn set_on_complete(&mut self, on_complete: FnOnComplete) {
-        self.on_complete_cb = on_complete;
-    }
 }
@@ -0,0 +1,88 @@
+extern crate behave;
+
+use behave::helpers::*;
+use behave::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+struct YieldFor(u32);
+
+impl Future for YieldFor {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 == 0 {
+            Poll::Ready(())
+        } else {
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn pending_futures_keep_the_node_running() {
+    let mut tree = Tree::new(async_action(|| async {
+        YieldFor(2).await;
+        Status::Success
+    }));
+    tree.start();
+    assert!(tree.step());
+    assert_eq!(tree.status(), Status::Running);
+    assert!(tree.step());
+    assert_eq!(tree.status(), Status::Running);
+    assert!(tree.step());
+    assert_eq!(tree.status(), Status::Success);
+    assert!(!tree.step());
+}
+
+#[test]
+fn boolean_outputs_map_to_completion() {
+    let mut tree = Tree::new(selector(vec![
+        async_action(|| async { false }),
+        async_action(|| async {
+            YieldFor(1).await;
+            true
+        }),
+    ]));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.run(), Status::Success);
+}
+
+#[test]
+fn abort_drops_the_future() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = dropped.clone();
+    let mut tree = Tree::new(sequence(vec![async_action(move || {
+        let guard = DropFlag(flag.clone());
+        async move {
+            YieldFor(10).await;
+            drop(guard);
+            Status::Success
+        }
+    })]));
+    tree.start();
+    tree.step();
+    tree.step();
+    assert!(!dropped.load(Ordering::SeqCst));
+
+    let node = tree.node_mut(NodeId(1)).unwrap();
+    assert_eq!(node.abort(), Status::Aborted);
+    assert!(dropped.load(Ordering::SeqCst));
+    let steps = tree.steps();
+    while tree.step() {}
+    assert_eq!(tree.steps(), steps);
+    assert_eq!(tree.node(NodeId(1)).unwrap().status(), Status::Aborted);
+}
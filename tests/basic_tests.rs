@@ -4,15 +4,14 @@ mod action;
 mod sequence {
     use crate::action::CallCounterAction;
     use behave::helpers::*;
+    use behave::sync::{handle, SharedCell};
     use behave::*;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
     #[test]
     fn exit_on_first_child_failure() {
-        let a1 = Rc::new(RefCell::new(CallCounterAction::new(Status::Success)));
-        let a2 = Rc::new(RefCell::new(CallCounterAction::new(Status::Failure)));
-        let a3 = Rc::new(RefCell::new(CallCounterAction::new(Status::Success)));
+        let a1 = handle(CallCounterAction::new(Status::Success));
+        let a2 = handle(CallCounterAction::new(Status::Failure));
+        let a3 = handle(CallCounterAction::new(Status::Success));
 
         let mut tree = Tree::new(sequence(vec![
             custom(a1.clone()),
@@ -21,9 +20,9 @@ mod sequence {
         ]));
         tree.run();
 
-        assert_eq!(a1.borrow().call_count, 1);
-        assert_eq!(a2.borrow().call_count, 1);
-        assert_eq!(a3.borrow().call_count, 0);
+        assert_eq!(a1.read(|node| node.call_count), 1);
+        assert_eq!(a2.read(|node| node.call_count), 1);
+        assert_eq!(a3.read(|node| node.call_count), 0);
     }
     #[test]
     fn fail_if_any_fail() {
@@ -42,20 +41,30 @@ mod sequence {
         ]));
         assert_eq!(Status::Success, tree.run())
     }
+
+    #[test]
+    fn nested_sequence_completes_once() {
+        let a = handle(CallCounterAction::new(Status::Failure));
+        let mut tree = Tree::new(sequence(vec![
+            sequence(vec![action(|| Status::Success), action(|| Status::Success)]),
+            custom(a.clone()),
+        ]));
+        assert_eq!(Status::Failure, tree.run());
+        assert_eq!(a.read(|node| node.call_count), 1);
+    }
 }
 
 mod selector {
     use crate::action::CallCounterAction;
     use behave::helpers::*;
+    use behave::sync::{handle, SharedCell};
     use behave::*;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
     #[test]
     fn exit_on_first_child_success() {
-        let a1 = Rc::new(RefCell::new(CallCounterAction::new(Status::Success)));
-        let a2 = Rc::new(RefCell::new(CallCounterAction::new(Status::Failure)));
-        let a3 = Rc::new(RefCell::new(CallCounterAction::new(Status::Success)));
+        let a1 = handle(CallCounterAction::new(Status::Success));
+        let a2 = handle(CallCounterAction::new(Status::Failure));
+        let a3 = handle(CallCounterAction::new(Status::Success));
 
         let mut tree = Tree::new(selector(vec![
             custom(a1.clone()),
@@ -64,9 +73,9 @@ mod selector {
         ]));
         tree.run();
 
-        assert_eq!(a1.borrow().call_count, 1);
-        assert_eq!(a2.borrow().call_count, 0);
-        assert_eq!(a3.borrow().call_count, 0);
+        assert_eq!(a1.read(|node| node.call_count), 1);
+        assert_eq!(a2.read(|node| node.call_count), 0);
+        assert_eq!(a3.read(|node| node.call_count), 0);
     }
     #[test]
     fn fail_if_all_fail() {
@@ -90,15 +99,99 @@ mod selector {
 mod decorators {
     use crate::action::CallCounterAction;
     use behave::helpers::*;
+    use behave::sync::{handle, SharedCell};
     use behave::*;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
     #[test]
     pub fn repeater_count() {
-        let a = Rc::new(RefCell::new(CallCounterAction::new(Status::Success)));
+        let a = handle(CallCounterAction::new(Status::Success));
         let mut tree = Tree::new(repeater(custom(a.clone()), 3));
         assert_eq!(Status::Success, tree.run());
-        assert_eq!(a.borrow().call_count, 3);
+        assert_eq!(a.read(|node| node.call_count), 3);
+    }
+
+    #[test]
+    pub fn repeater_restarts_composite() {
+        let a = handle(CallCounterAction::new(Status::Success));
+        let mut tree = Tree::new(repeater(
+            sequence(vec![action(|| Status::Success), custom(a.clone())]),
+            3,
+        ));
+        assert_eq!(Status::Success, tree.run());
+        assert_eq!(a.read(|node| node.call_count), 3);
+        assert_eq!(Status::Success, tree.run());
+        assert_eq!(a.read(|node| node.call_count), 6);
+    }
+}
+
+mod wait {
+    use behave::helpers::*;
+    use behave::*;
+
+    #[test]
+    fn runs_for_ticks_then_succeeds() {
+        let mut tree = Tree::new(wait(3));
+        assert_eq!(Status::Success, tree.run());
+        assert_eq!(tree.steps(), 4);
+    }
+}
+
+mod arena {
+    use behave::helpers::*;
+    use behave::*;
+
+    struct Inverter {
+        status: Status,
+    }
+
+    impl Behavior for Inverter {
+        fn initialize(&mut self, context: &mut Context) {
+            self.status = Status::Running;
+            context.start_child(0);
+        }
+
+        fn status(&self) -> Status {
+            self.status
+        }
+
+        fn child_complete(&mut self, _: usize, result: Status, _: &mut Context) -> Status {
+            match result {
+                Status::Success => Status::Failure,
+                _ => Status::Success,
+            }
+        }
+
+        fn on_complete(&mut self, result: Status) {
+            self.status = result;
+        }
+    }
+
+    fn inverter(child: NodeBuilder) -> NodeBuilder {
+        NodeBuilder::new(Inverter {
+            status: Status::Invalid,
+        })
+        .with_children(vec![child])
+    }
+
+    #[test]
+    fn custom_composites_drive_children_through_context() {
+        let mut tree = Tree::new(sequence(vec![
+            inverter(action(|| Status::Failure)),
+            inverter(inverter(action(|| Status::Success))),
+        ]));
+        assert_eq!(Status::Success, tree.run());
+        assert_eq!(tree.node(NodeId(4)).unwrap().kind(), "Inverter");
+    }
+
+    #[test]
+    fn structure_is_indexed_by_node_id() {
+        let tree = Tree::new(sequence(vec![
+            selector(vec![wait(1), wait(2)]),
+            action(|| Status::Success),
+        ]));
+        assert_eq!(tree.children(NodeId(0)), &[NodeId(1), NodeId(4)]);
+        assert_eq!(tree.children(NodeId(1)), &[NodeId(2), NodeId(3)]);
+        assert!(tree.children(NodeId(4)).is_empty());
+        assert_eq!(tree.parent(NodeId(3)), Some(NodeId(1)));
     }
 }
@@ -0,0 +1,272 @@
+extern crate behave;
+
+use behave::blackboard::AbortMode;
+use behave::helpers::*;
+use behave::nodes::Sequence;
+use behave::observer::{Recorder, TraceEvent};
+use behave::registry::Param;
+use behave::sync::{handle, SharedCell};
+use behave::*;
+
+fn enemy(value: Option<&Param>) -> bool {
+    value == Some(&Param::Bool(true))
+}
+
+struct Alarm {
+    status: Status,
+}
+
+impl Behavior for Alarm {
+    fn update(&mut self, context: &mut Context) -> Status {
+        context.blackboard_mut().set("enemy", Param::Bool(true));
+        self.status = Status::Success;
+        self.status
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+}
+
+// A sequence that reports itself as a selector.
+struct Impostor(Sequence);
+
+impl Behavior for Impostor {
+    fn initialize(&mut self, context: &mut Context) {
+        self.0.initialize(context)
+    }
+
+    fn status(&self) -> Status {
+        self.0.status()
+    }
+
+    fn child_complete(&mut self, child: usize, result: Status, context: &mut Context) -> Status {
+        self.0.child_complete(child, result, context)
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.0.on_complete(result)
+    }
+
+    fn kind(&self) -> &'static str {
+        "Selector"
+    }
+}
+
+#[test]
+fn self_abort_fails_the_running_branch() {
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition("enemy", AbortMode::SelfOnly, enemy, wait(100)),
+        action(|| Status::Success),
+    ]));
+    tree.set_blackboard("enemy", Param::Bool(true));
+    tree.start();
+    for _ in 0..5 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(2)).unwrap().status(), Status::Running);
+
+    tree.set_blackboard("enemy", Param::Bool(false));
+    assert_eq!(tree.node(NodeId(2)).unwrap().status(), Status::Aborted);
+    assert_eq!(tree.node(NodeId(1)).unwrap().status(), Status::Failure);
+    assert_eq!(tree.pending(), vec![NodeId(3)]);
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+}
+
+#[test]
+fn lower_priority_abort_restarts_the_selector() {
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition(
+            "enemy",
+            AbortMode::LowerPriority,
+            enemy,
+            action(|| Status::Success),
+        ),
+        wait(100),
+    ]));
+    tree.start();
+    for _ in 0..5 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+
+    tree.set_blackboard("enemy", Param::Bool(true));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Aborted);
+    assert_eq!(tree.node(NodeId(1)).unwrap().status(), Status::Running);
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+    assert!(tree.steps() < 20);
+}
+
+#[test]
+fn unchanged_results_do_not_abort() {
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition("enemy", AbortMode::Both, |value| value.is_some(), wait(3)),
+        action(|| Status::Failure),
+    ]));
+    tree.set_blackboard("enemy", Param::Bool(true));
+    tree.start();
+    tree.step();
+    tree.set_blackboard("enemy", Param::Bool(false));
+    assert_eq!(tree.node(NodeId(2)).unwrap().status(), Status::Running);
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+}
+
+#[test]
+fn nodes_can_write_the_blackboard() {
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition(
+            "enemy",
+            AbortMode::LowerPriority,
+            enemy,
+            action(|| Status::Success),
+        ),
+        sequence(vec![
+            NodeBuilder::new(Alarm {
+                status: Status::Invalid,
+            }),
+            wait(100),
+        ]),
+    ]));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("enemy"), Some(&Param::Bool(true)));
+    assert_eq!(tree.node(NodeId(5)).unwrap().status(), Status::Aborted);
+}
+
+#[test]
+fn aborted_leaves_run_again_after_a_restart() {
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition("enemy", AbortMode::LowerPriority, enemy, wait(1)),
+        action(|| Status::Running),
+    ]));
+    tree.start();
+    for _ in 0..3 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+    tree.set_blackboard("enemy", Param::Bool(true));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Aborted);
+    tree.set_blackboard("enemy", Param::Bool(false));
+
+    tree.start();
+    for _ in 0..5 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+    assert_eq!(tree.pending(), vec![NodeId(3)]);
+}
+
+#[test]
+fn only_a_flipped_result_aborts() {
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition(
+            "enemy",
+            AbortMode::LowerPriority,
+            |value| value.is_some(),
+            action(|| Status::Failure),
+        ),
+        wait(100),
+    ]));
+    tree.set_blackboard("enemy", Param::Int(1));
+    tree.start();
+    for _ in 0..4 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+    tree.set_blackboard("enemy", Param::Int(2));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+}
+
+#[test]
+fn lower_priority_only_applies_under_selectors() {
+    let one = |value: Option<&Param>| value == Some(&Param::Int(1));
+    let mut tree = Tree::new(sequence(vec![
+        blackboard_condition(
+            "k",
+            AbortMode::LowerPriority,
+            one,
+            action(|| Status::Success),
+        ),
+        wait(100),
+    ]));
+    tree.set_blackboard("k", Param::Int(1));
+    tree.start();
+    for _ in 0..4 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+    tree.set_blackboard("k", Param::Int(2));
+    tree.set_blackboard("k", Param::Int(1));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+}
+
+#[test]
+fn lower_priority_ignores_the_parent_kind() {
+    let one = |value: Option<&Param>| value == Some(&Param::Int(1));
+    let mut tree = Tree::new(
+        NodeBuilder::new(Impostor(Sequence::new())).with_children(vec![
+            blackboard_condition(
+                "k",
+                AbortMode::LowerPriority,
+                one,
+                action(|| Status::Success),
+            ),
+            wait(100),
+        ]),
+    );
+    tree.set_blackboard("k", Param::Int(1));
+    tree.start();
+    for _ in 0..4 {
+        tree.step();
+    }
+    tree.set_blackboard("k", Param::Int(2));
+    tree.set_blackboard("k", Param::Int(1));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+}
+
+#[test]
+fn aborts_dequeue_leaves_that_have_not_run_yet() {
+    let recorder = handle(Recorder::new());
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition("enemy", AbortMode::LowerPriority, enemy, wait(3)),
+        sequence(vec![
+            NodeBuilder::new(Alarm {
+                status: Status::Invalid,
+            }),
+            action(|| Status::Success),
+        ]),
+    ]));
+    tree.add_observer(recorder.clone());
+    tree.start();
+    while tree.pending() != vec![NodeId(4)] {
+        tree.step();
+    }
+    tree.step();
+    assert_eq!(tree.node(NodeId(5)).unwrap().status(), Status::Aborted);
+    assert!(!tree.pending().contains(&NodeId(5)));
+
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+    let completions: Vec<_> = recorder
+        .read(Recorder::events)
+        .into_iter()
+        .filter_map(|event| match event {
+            TraceEvent::ChildComplete {
+                parent: NodeId(0),
+                child,
+                status,
+            } => Some((child, status)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        completions,
+        vec![(NodeId(1), Status::Failure), (NodeId(1), Status::Success)]
+    );
+}
@@ -0,0 +1,250 @@
+#![cfg(feature = "debug-server")]
+extern crate behave;
+
+use behave::debug::*;
+use behave::helpers::*;
+use behave::registry::Param;
+use behave::*;
+use std::io::Write;
+
+fn connect(server: &mut Server) -> Client {
+    let client = Client::connect_tcp(server.local_addr().unwrap()).unwrap();
+    while server.client_count() == 0 {
+        server.poll();
+    }
+    client
+}
+
+fn recv_until<F>(client: &mut Client, done: F) -> Vec<ServerMessage>
+where
+    F: Fn(&ServerMessage) -> bool,
+{
+    let mut messages = vec![];
+    loop {
+        let message = client.recv().unwrap();
+        let finished = done(&message);
+        messages.push(message);
+        if finished {
+            return messages;
+        }
+    }
+}
+
+#[test]
+fn sends_structure_on_connect() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    let mut client = connect(&mut server);
+    match client.recv().unwrap() {
+        ServerMessage::Tree { nodes } => {
+            assert_eq!(nodes.len(), 3);
+            assert_eq!(nodes[0].name, "guard");
+            assert_eq!(nodes[0].kind, "Selector");
+            assert_eq!(nodes[2].parent, Some(NodeId(0)));
+            assert_eq!(nodes[2].kind, "Wait");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn streams_transitions_until_finished() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    let mut client = connect(&mut server);
+    client.recv().unwrap();
+    for _ in 0..10 {
+        server.poll();
+    }
+    let messages = recv_until(&mut client, |m| matches!(m, ServerMessage::Finished { .. }));
+    assert!(messages.contains(&ServerMessage::Transition {
+        step: 2,
+        node: NodeId(1),
+        from: Status::Invalid,
+        to: Status::Failure,
+    }));
+    assert_eq!(
+        messages.last(),
+        Some(&ServerMessage::Finished {
+            status: Status::Success
+        })
+    );
+}
+
+#[test]
+fn finished_trees_wait_for_a_restart() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    server.tree_mut().set_blackboard("alarm", Param::Bool(true));
+    for _ in 0..10 {
+        server.poll();
+    }
+    assert_eq!(server.tree().status(), Status::Success);
+    let steps = server.tree().steps();
+    server.poll();
+    assert_eq!(server.tree().steps(), steps);
+    assert_eq!(
+        server.tree().blackboard().get("alarm"),
+        Some(&Param::Bool(true))
+    );
+
+    server.restart_on_finish(true);
+    server.poll();
+    assert!(server.tree().steps() > steps);
+}
+
+#[test]
+fn pause_break_step_and_resume() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    let mut client = connect(&mut server);
+    client.recv().unwrap();
+
+    client.send(&Command::Pause).unwrap();
+    client
+        .send(&Command::Break {
+            node: Some(NodeId(2)),
+            to: None,
+        })
+        .unwrap();
+    server.poll();
+    let messages = recv_until(&mut client, |m| *m == ServerMessage::State { paused: true });
+    assert!(messages[..messages.len() - 1].iter().all(|m| matches!(
+        m,
+        ServerMessage::Transition { .. } | ServerMessage::Update { .. }
+    )));
+    assert_eq!(client.recv().unwrap(), ServerMessage::Ok);
+    assert!(server.is_paused());
+
+    client.send(&Command::Resume).unwrap();
+    while !server.is_paused() || server.debugger().next_node() != Some(NodeId(2)) {
+        server.poll();
+    }
+    let messages = recv_until(&mut client, |m| matches!(m, ServerMessage::Paused { .. }));
+    assert_eq!(messages[0], ServerMessage::State { paused: false });
+    assert_eq!(
+        messages.last(),
+        Some(&ServerMessage::Paused { node: NodeId(2) })
+    );
+
+    client.send(&Command::Step).unwrap();
+    server.poll();
+    let messages = recv_until(&mut client, |m| matches!(m, ServerMessage::State { .. }));
+    assert!(messages.contains(&ServerMessage::Update {
+        step: 3,
+        node: NodeId(2),
+        status: Status::Running,
+    }));
+
+    client
+        .send(&Command::Delete {
+            node: None,
+            to: None,
+        })
+        .unwrap();
+    server.poll();
+    assert!(matches!(
+        client.recv().unwrap(),
+        ServerMessage::Error { .. }
+    ));
+}
+
+#[test]
+fn rejects_malformed_commands() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    let mut client = connect(&mut server);
+    client.recv().unwrap();
+    client.send(&Command::Pause).unwrap();
+    server.poll();
+    client.recv().unwrap();
+
+    let mut raw = std::net::TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    std::io::Write::write_all(&mut raw, b"{\"command\":\"explode\"}\n").unwrap();
+    server.poll();
+    server.poll();
+    let mut reader = std::io::BufReader::new(raw);
+    let mut line = String::new();
+    std::io::BufRead::read_line(&mut reader, &mut line).unwrap();
+    assert!(line.contains("\"type\":\"tree\""));
+    line.clear();
+    std::io::BufRead::read_line(&mut reader, &mut line).unwrap();
+    assert!(line.starts_with("{\"type\":\"error\""), "{}", line);
+}
+
+#[cfg(unix)]
+#[test]
+fn serves_unix_sockets() {
+    let path = std::env::temp_dir().join(format!("behave-debug-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut server = Server::bind_unix(&path, Tree::new(action(|| Status::Success))).unwrap();
+    let mut client = Client::connect_unix(&path).unwrap();
+    while server.client_count() == 0 {
+        server.poll();
+    }
+    assert!(matches!(client.recv().unwrap(), ServerMessage::Tree { .. }));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn drops_clients_that_stop_reading() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    let mut stalled = std::net::TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    while server.client_count() == 0 {
+        server.poll();
+    }
+    let commands = "{\"command\":\"tree\"}\n".repeat(100);
+    for _ in 0..100_000 {
+        if server.client_count() == 0 {
+            break;
+        }
+        if stalled.write_all(commands.as_bytes()).is_err() {
+            break;
+        }
+        server.poll();
+    }
+    assert_eq!(server.client_count(), 0);
+}
+
+#[test]
+fn drops_clients_that_never_end_a_line() {
+    let tree = Tree::new(named(
+        "guard",
+        selector(vec![action(|| Status::Failure), wait(1)]),
+    ));
+    let mut server = Server::bind_tcp("127.0.0.1:0", tree).unwrap();
+    let mut flooding = std::net::TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    while server.client_count() == 0 {
+        server.poll();
+    }
+    let garbage = vec![b'x'; 1 << 12];
+    for _ in 0..100_000 {
+        if server.client_count() == 0 {
+            break;
+        }
+        if flooding.write_all(&garbage).is_err() {
+            break;
+        }
+        server.poll();
+    }
+    assert_eq!(server.client_count(), 0);
+}
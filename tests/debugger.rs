@@ -0,0 +1,116 @@
+extern crate behave;
+
+use behave::debugger::*;
+use behave::helpers::*;
+use behave::*;
+
+#[test]
+fn pauses_before_node_breakpoint() {
+    let mut debugger = Debugger::new(Tree::new(selector(vec![
+        sequence(vec![condition(|| false), action(|| Status::Success)]),
+        repeater(action(|| Status::Success), 2),
+    ])));
+    debugger.add_breakpoint(Breakpoint::Node(NodeId(5)));
+    assert_eq!(debugger.resume(), Stop::BeforeUpdate(NodeId(5)));
+    assert_eq!(debugger.next_node(), Some(NodeId(5)));
+    assert_eq!(
+        debugger.active_path(),
+        vec![NodeId(0), NodeId(4), NodeId(5)]
+    );
+    assert_eq!(debugger.status(NodeId(1)), Some(Status::Failure));
+
+    assert_eq!(debugger.resume(), Stop::BeforeUpdate(NodeId(5)));
+    assert_eq!(debugger.resume(), Stop::Finished(Status::Success));
+}
+
+#[test]
+fn pauses_on_status_transition() {
+    let mut debugger = Debugger::new(Tree::new(selector(vec![
+        sequence(vec![condition(|| false), action(|| Status::Success)]),
+        repeater(action(|| Status::Success), 2),
+    ])));
+    debugger.add_breakpoint(Breakpoint::Transition {
+        node: Some(NodeId(2)),
+        to: Status::Failure,
+    });
+    assert_eq!(
+        debugger.resume(),
+        Stop::Transition {
+            node: NodeId(2),
+            from: Status::Invalid,
+            to: Status::Failure,
+        }
+    );
+    assert!(debugger.remove_breakpoint(Breakpoint::Transition {
+        node: Some(NodeId(2)),
+        to: Status::Failure,
+    }));
+    debugger.add_breakpoint(Breakpoint::Transition {
+        node: None,
+        to: Status::Success,
+    });
+    assert_eq!(
+        debugger.resume(),
+        Stop::Transition {
+            node: NodeId(5),
+            from: Status::Invalid,
+            to: Status::Success,
+        }
+    );
+    debugger.remove_breakpoint(Breakpoint::Transition {
+        node: None,
+        to: Status::Success,
+    });
+    assert_eq!(debugger.resume(), Stop::Finished(Status::Success));
+}
+
+#[test]
+fn single_steps_through_queue() {
+    let mut debugger = Debugger::new(Tree::new(selector(vec![
+        sequence(vec![condition(|| false), action(|| Status::Success)]),
+        repeater(action(|| Status::Success), 2),
+    ])));
+    debugger.restart();
+    assert_eq!(debugger.queue(), vec![NodeId(0), NodeId(1), NodeId(2)]);
+    assert_eq!(debugger.step(), Stop::Stepped);
+    assert_eq!(debugger.queue(), vec![NodeId(1), NodeId(2)]);
+    let mut steps = 1;
+    while debugger.step() == Stop::Stepped {
+        steps += 1;
+    }
+    assert!(steps > 3);
+    assert_eq!(debugger.tree().status(), Status::Success);
+}
+
+#[test]
+fn text_commands() {
+    let mut debugger = Debugger::new(Tree::new(selector(vec![
+        sequence(vec![condition(|| false), action(|| Status::Success)]),
+        repeater(action(|| Status::Success), 2),
+    ])));
+    assert_eq!(
+        debugger.execute("break n3"),
+        "breakpoint set: Node(NodeId(3))"
+    );
+    assert_eq!(debugger.execute("continue"), "finished with Success");
+    debugger.execute("restart");
+    assert_eq!(debugger.execute("queue"), "n0 n1 n2");
+    assert_eq!(debugger.execute("path"), "n0");
+    assert!(debugger
+        .execute("tree")
+        .starts_with("> n0 Selector [Running]"));
+    assert!(debugger.execute("frobnicate").starts_with("commands:"));
+}
+
+#[test]
+fn waiting_trees_are_not_finished() {
+    let mut debugger = Debugger::new(Tree::new(sequence(vec![
+        wait_for("go"),
+        action(|| Status::Success),
+    ])));
+    assert_eq!(debugger.resume(), Stop::Waiting);
+    assert_eq!(debugger.step(), Stop::Waiting);
+    assert_eq!(debugger.tree().waiting(), vec![NodeId(1)]);
+    assert_eq!(debugger.notify("go"), 1);
+    assert_eq!(debugger.resume(), Stop::Finished(Status::Success));
+}
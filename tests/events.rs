@@ -0,0 +1,58 @@
+extern crate behave;
+
+use behave::helpers::*;
+use behave::*;
+
+#[test]
+fn waiting_nodes_leave_the_queue() {
+    let mut tree = Tree::new(sequence(vec![wait_for("door"), action(|| Status::Success)]));
+    tree.enable_profiling();
+    tree.start();
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Running);
+    assert_eq!(tree.waiting(), vec![NodeId(1)]);
+    assert!(!tree.step());
+
+    let ticks = tree
+        .profile_report()
+        .unwrap()
+        .node(NodeId(1))
+        .unwrap()
+        .ticks;
+    assert_eq!(ticks, 1);
+}
+
+#[test]
+fn notify_wakes_subscribers() {
+    let mut tree = Tree::new(sequence(vec![wait_for("door"), action(|| Status::Success)]));
+    tree.start();
+    while tree.step() {}
+
+    assert_eq!(tree.notify("window"), 0);
+    assert!(!tree.step());
+    assert_eq!(tree.notify("door"), 1);
+    assert!(tree.waiting().is_empty());
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+    assert_eq!(tree.notify("door"), 0);
+}
+
+#[test]
+fn idle_trees_are_not_restarted_by_tick() {
+    let mut tree = Tree::new(wait_for("go"));
+    assert_eq!(tree.tick(), Status::Running);
+    assert_eq!(tree.tick(), Status::Running);
+    assert_eq!(tree.steps(), 1);
+    tree.notify("go");
+    assert_eq!(tree.tick(), Status::Success);
+}
+
+#[test]
+fn restarting_clears_subscriptions() {
+    let mut tree = Tree::new(wait_for("go"));
+    tree.start();
+    tree.step();
+    tree.start();
+    assert!(tree.waiting().is_empty());
+    assert_eq!(tree.notify("go"), 0);
+}
@@ -0,0 +1,53 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::helpers::*;
+use behave::sync::handle;
+use behave::*;
+
+#[test]
+fn dot_lists_nodes_and_edges() {
+    let a = handle(CallCounterAction::new(Status::Failure));
+    let mut tree = Tree::new(selector(vec![
+        custom(a),
+        repeater(action(|| Status::Success), 2),
+    ]));
+    tree.run();
+    let dot = tree.to_dot();
+    assert!(dot.starts_with("digraph tree {\n"));
+    assert!(dot.contains("n0 [label=\"Selector\\nSuccess\", fillcolor=\"#93c47d\"];"));
+    assert!(dot.contains("n1 [label=\"CallCounterAction\\nFailure\", fillcolor=\"#e06666\"];"));
+    assert!(dot.contains("n2 [label=\"Repeater\\nSuccess\""));
+    assert!(dot.contains("n3 [label=\"Action\\nSuccess\""));
+    for edge in &["n0 -> n1;", "n0 -> n2;", "n2 -> n3;"] {
+        assert!(dot.contains(edge), "missing {}", edge);
+    }
+}
+
+#[test]
+fn mermaid_lists_nodes_and_edges() {
+    let a = handle(CallCounterAction::new(Status::Failure));
+    let tree = Tree::new(selector(vec![
+        custom(a),
+        repeater(action(|| Status::Success), 2),
+    ]));
+    let mermaid = tree.to_mermaid();
+    assert!(mermaid.starts_with("graph TD\n"));
+    assert!(mermaid.contains("n0[\"Selector<br/>Invalid\"]:::invalid"));
+    for edge in &["n0 --> n1", "n0 --> n2", "n2 --> n3"] {
+        assert!(mermaid.contains(edge), "missing {}", edge);
+    }
+    assert!(mermaid.contains("classDef running fill:#ffd966"));
+}
+
+#[test]
+fn labels_escape_hostile_names() {
+    let tree = Tree::new(named("a\\\"b\n<i>]#", wait(1)));
+    assert!(tree
+        .to_dot()
+        .contains("n0 [label=\"a\\\\\\\"b\\n<i>]# (Wait)\\nInvalid\""));
+    assert!(tree
+        .to_mermaid()
+        .contains("n0[\"a\\#quot;b<br/>#lt;i#gt;#93;#35; (Wait)<br/>Invalid\"]"));
+}
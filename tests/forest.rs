@@ -0,0 +1,82 @@
+extern crate behave;
+
+use behave::forest::Forest;
+use behave::helpers::*;
+use behave::*;
+
+#[test]
+fn tick_advances_one_frame() {
+    let mut tree = Tree::new(sequence(vec![
+        action(|| Status::Success),
+        action(|| Status::Success),
+    ]));
+    assert_eq!(tree.tick(), Status::Running);
+    assert_eq!(tree.tick(), Status::Success);
+    assert_eq!(tree.tick(), Status::Running);
+}
+
+#[test]
+fn ticks_every_tree() {
+    let mut forest: Forest = (0..100).map(|i| Tree::new(wait(i % 3))).collect();
+    assert_eq!(forest.len(), 100);
+
+    let statuses = forest.tick();
+    assert_eq!(statuses.len(), 100);
+    assert_eq!(statuses[0].1, Status::Success);
+    assert_eq!(statuses[1].1, Status::Running);
+    assert_eq!(statuses[2].1, Status::Running);
+
+    let statuses = forest.tick();
+    assert_eq!(statuses[1].1, Status::Success);
+    assert_eq!(statuses[2].1, Status::Running);
+    assert_eq!(statuses[3].1, Status::Success);
+
+    let statuses = forest.tick();
+    assert_eq!(statuses[2].1, Status::Success);
+}
+
+#[test]
+fn trees_can_be_added_and_removed() {
+    let mut forest = Forest::new();
+    assert!(forest.is_empty());
+    let failing = forest.add(Tree::new(action(|| Status::Failure)));
+    let succeeding = forest.add(Tree::new(action(|| Status::Success)));
+    assert_eq!(
+        forest.tick(),
+        vec![(failing, Status::Failure), (succeeding, Status::Success)]
+    );
+    assert!(forest.remove(failing).is_some());
+    assert!(forest.remove(failing).is_none());
+    assert_eq!(forest.tick(), vec![(succeeding, Status::Success)]);
+    assert_eq!(forest.get(succeeding).unwrap().steps(), 2);
+    assert_eq!(forest.len(), 1);
+}
+
+#[test]
+fn handles_survive_removing_other_trees() {
+    let mut forest = Forest::new();
+    let first = forest.add(Tree::new(wait(1)));
+    let second = forest.add(Tree::new(wait(2)));
+    let third = forest.add(Tree::new(wait(3)));
+    forest.remove(first);
+    assert!(forest.get(first).is_none());
+    assert_eq!(forest.get(second).unwrap().steps(), 0);
+    forest.get_mut(third).unwrap().tick();
+    assert_eq!(forest.get(third).unwrap().steps(), 1);
+    assert_eq!(forest.get(second).unwrap().steps(), 0);
+    assert_eq!(forest.iter().count(), 2);
+}
+
+#[test]
+fn stale_handles_miss_reused_slots() {
+    let mut forest = Forest::new();
+    let removed = forest.add(Tree::new(wait(1)));
+    forest.remove(removed);
+    let added = forest.add(Tree::new(wait(2)));
+    assert_ne!(added, removed);
+    assert!(forest.get(removed).is_none());
+    assert!(forest.get_mut(removed).is_none());
+    assert!(forest.remove(removed).is_none());
+    assert_eq!(forest.tick(), vec![(added, Status::Running)]);
+    assert_eq!(forest.get(added).unwrap().steps(), 1);
+}
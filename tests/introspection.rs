@@ -0,0 +1,110 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::helpers::*;
+use behave::sync::handle;
+use behave::*;
+
+#[test]
+fn walk_visits_nodes_in_preorder() {
+    let eat = handle(CallCounterAction::new(Status::Success));
+    let tree = Tree::new(named(
+        "root",
+        sequence(vec![
+            named("hungry", condition(|| true)),
+            selector(vec![
+                named("eat", custom(eat)),
+                repeater(action(|| Status::Success), 2),
+            ]),
+        ]),
+    ));
+    let mut visited = vec![];
+    tree.walk(|visit| {
+        let node = visit.node;
+        visited.push((
+            visit.id,
+            visit.parent,
+            visit.depth,
+            node.kind(),
+            node.name().to_string(),
+        ));
+    });
+    assert_eq!(
+        visited,
+        vec![
+            (NodeId(0), None, 0, "Sequence", "root".to_string()),
+            (
+                NodeId(1),
+                Some(NodeId(0)),
+                1,
+                "Action",
+                "hungry".to_string()
+            ),
+            (
+                NodeId(2),
+                Some(NodeId(0)),
+                1,
+                "Selector",
+                "Selector".to_string()
+            ),
+            (
+                NodeId(3),
+                Some(NodeId(2)),
+                2,
+                "CallCounterAction",
+                "eat".to_string()
+            ),
+            (
+                NodeId(4),
+                Some(NodeId(2)),
+                2,
+                "Repeater",
+                "Repeater".to_string()
+            ),
+            (
+                NodeId(5),
+                Some(NodeId(4)),
+                3,
+                "Action",
+                "Action".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn children_reflect_structure() {
+    let eat = handle(CallCounterAction::new(Status::Success));
+    let tree = Tree::new(named(
+        "root",
+        sequence(vec![
+            named("hungry", condition(|| true)),
+            selector(vec![
+                named("eat", custom(eat)),
+                repeater(action(|| Status::Success), 2),
+            ]),
+        ]),
+    ));
+    let mut counts = vec![];
+    tree.walk(|visit| counts.push(tree.children(visit.id).len()));
+    assert_eq!(counts, vec![2, 0, 2, 0, 1, 0]);
+}
+
+#[test]
+fn exports_use_names() {
+    let eat = handle(CallCounterAction::new(Status::Success));
+    let tree = Tree::new(named(
+        "root",
+        sequence(vec![
+            named("hungry", condition(|| true)),
+            selector(vec![
+                named("eat", custom(eat)),
+                repeater(action(|| Status::Success), 2),
+            ]),
+        ]),
+    ));
+    let dot = tree.to_dot();
+    assert!(dot.contains("n0 [label=\"root (Sequence)\\nInvalid\""));
+    assert!(dot.contains("n3 [label=\"eat (CallCounterAction)\\nInvalid\""));
+}
@@ -0,0 +1,68 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::helpers::*;
+use behave::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+struct Tracked {
+    drops: Arc<AtomicUsize>,
+    status: Status,
+}
+
+impl Behavior for Tracked {
+    fn update(&mut self, _: &mut Context) -> Status {
+        self.status = Status::Success;
+        self.status
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn tracked(drops: &Arc<AtomicUsize>) -> NodeBuilder {
+    NodeBuilder::new(Tracked {
+        drops: drops.clone(),
+        status: Status::Invalid,
+    })
+}
+
+#[test]
+fn nodes_are_dropped_with_the_tree() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    for _ in 0..1000 {
+        let mut tree = Tree::new(repeater(
+            sequence(vec![
+                tracked(&drops),
+                selector(vec![action(|| Status::Failure), tracked(&drops)]),
+            ]),
+            2,
+        ));
+        tree.enable_profiling();
+        assert_eq!(Status::Success, tree.run());
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 2000);
+}
+
+#[test]
+fn shared_custom_nodes_are_released() {
+    let node = Arc::new(Mutex::new(CallCounterAction::new(Status::Success)));
+    let mut tree = Tree::new(sequence(vec![custom(node.clone())]));
+    tree.run();
+    assert_eq!(Arc::strong_count(&node), 2);
+    drop(tree);
+    assert_eq!(Arc::strong_count(&node), 1);
+}
@@ -0,0 +1,45 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::sync::{handle, SharedCell};
+use behave::*;
+
+fn find_food() -> NodeBuilder {
+    helpers::action(|| Status::Success)
+}
+
+#[test]
+fn nested_composites() {
+    let is_hungry = || true;
+    let eat = handle(CallCounterAction::new(Status::Failure));
+    let mut tree = Tree::new(tree! {
+        sequence {
+            cond(is_hungry),
+            selector { custom(eat.clone()), find_food() },
+        }
+    });
+    assert_eq!(Status::Success, tree.run());
+    assert_eq!(eat.read(|node| node.call_count), 1);
+}
+
+#[test]
+fn failed_condition_stops_sequence() {
+    let is_hungry = || false;
+    let eat = handle(CallCounterAction::new(Status::Success));
+    let mut tree = Tree::new(tree! {
+        sequence { cond(is_hungry), custom(eat.clone()) }
+    });
+    assert_eq!(Status::Failure, tree.run());
+    assert_eq!(eat.read(|node| node.call_count), 0);
+}
+
+#[test]
+fn decorator_wraps_single_child() {
+    let a = handle(CallCounterAction::new(Status::Success));
+    let mut tree = Tree::new(tree! {
+        repeater(3) { custom(a.clone()) }
+    });
+    assert_eq!(Status::Success, tree.run());
+    assert_eq!(a.read(|node| node.call_count), 3);
+}
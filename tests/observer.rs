@@ -0,0 +1,111 @@
+extern crate behave;
+
+use behave::blackboard::AbortMode;
+use behave::helpers::*;
+use behave::observer::*;
+use behave::registry::Param;
+use behave::sync::{handle, SharedCell};
+use behave::*;
+
+#[test]
+fn records_selector_decisions() {
+    let recorder = handle(Recorder::new());
+    let mut tree = Tree::new(selector(vec![
+        action(|| Status::Failure),
+        action(|| Status::Success),
+    ]));
+    tree.add_observer(recorder.clone());
+    assert_eq!(Status::Success, tree.run());
+
+    let events = recorder.read(Recorder::events);
+    let completions: Vec<_> = events
+        .iter()
+        .filter(|event| matches!(event, TraceEvent::ChildComplete { .. }))
+        .collect();
+    assert_eq!(
+        completions,
+        vec![
+            &TraceEvent::ChildComplete {
+                parent: NodeId(0),
+                child: NodeId(1),
+                status: Status::Failure,
+            },
+            &TraceEvent::ChildComplete {
+                parent: NodeId(0),
+                child: NodeId(2),
+                status: Status::Success,
+            },
+        ]
+    );
+    let initialized: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            TraceEvent::Initialize { node } => Some(*node),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(initialized, vec![NodeId(0), NodeId(1), NodeId(2)]);
+    assert!(events.contains(&TraceEvent::StatusChange {
+        node: NodeId(0),
+        from: Status::Running,
+        to: Status::Success,
+    }));
+}
+
+#[test]
+fn records_are_ordered() {
+    let recorder = handle(Recorder::new());
+    let mut tree = Tree::new(selector(vec![
+        action(|| Status::Failure),
+        action(|| Status::Success),
+    ]));
+    tree.add_observer(recorder.clone());
+    tree.run();
+
+    recorder.read(|recorder| {
+        for pair in recorder.records.windows(2) {
+            assert!(pair[0].step <= pair[1].step);
+            assert!(pair[0].time <= pair[1].time);
+        }
+    });
+}
+
+#[test]
+fn stdout_logger_attaches() {
+    let mut tree = Tree::new(selector(vec![
+        action(|| Status::Failure),
+        action(|| Status::Success),
+    ]));
+    tree.add_observer(StdoutLogger::new());
+    assert_eq!(Status::Success, tree.run());
+}
+
+#[test]
+fn records_aborted_nodes() {
+    let recorder = handle(Recorder::new());
+    let mut tree = Tree::new(selector(vec![
+        blackboard_condition(
+            "enemy",
+            AbortMode::SelfOnly,
+            |value| value == Some(&Param::Bool(true)),
+            sequence(vec![wait(100)]),
+        ),
+        action(|| Status::Success),
+    ]));
+    tree.add_observer(recorder.clone());
+    tree.set_blackboard("enemy", Param::Bool(true));
+    tree.start();
+    tree.step();
+    tree.step();
+    tree.set_blackboard("enemy", Param::Bool(false));
+
+    let aborted: Vec<_> = recorder
+        .read(Recorder::events)
+        .iter()
+        .filter_map(|event| match event {
+            TraceEvent::Abort { node } => Some(*node),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(aborted, vec![NodeId(2), NodeId(3)]);
+}
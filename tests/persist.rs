@@ -0,0 +1,283 @@
+extern crate behave;
+
+mod action;
+
+use action::CallCounterAction;
+use behave::blackboard::{AbortMode, Scope};
+use behave::helpers::*;
+use behave::persist::*;
+use behave::registry::{Param, ParamError};
+use behave::sync::{handle, Handle, SharedCell};
+use behave::*;
+
+#[derive(Behavior)]
+#[behave(persist)]
+struct Counter {
+    count: i64,
+    #[behave(status)]
+    status: Status,
+}
+
+impl Counter {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            status: Status::Invalid,
+        }
+    }
+}
+
+impl Update for Counter {
+    fn update(&mut self, _: &mut Context) -> Status {
+        self.count += 1;
+        if self.count >= 3 {
+            Status::Success
+        } else {
+            Status::Running
+        }
+    }
+}
+
+impl Persist for Counter {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status).with("count", Param::Int(self.count))
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        self.count = state.values.get_int("count")?;
+        self.status = state.status;
+        Ok(())
+    }
+}
+
+fn finish(tree: &mut Tree) -> Status {
+    while tree.step() {}
+    tree.status()
+}
+
+#[test]
+fn restored_tree_resumes_mid_execution() {
+    let tree = |counter: Handle<Counter>| {
+        Tree::new(repeater(
+            sequence(vec![wait(1), action(|| Status::Success), custom(counter)]),
+            2,
+        ))
+    };
+    let mut original = tree(handle(Counter::new()));
+    original.start();
+    for _ in 0..7 {
+        original.step();
+    }
+    let snapshot = original.snapshot();
+    assert_eq!(snapshot.steps, 7);
+    assert!(!snapshot.queue.is_empty());
+    assert_eq!(
+        snapshot.nodes[1]
+            .state
+            .as_ref()
+            .unwrap()
+            .values
+            .get_int("current_child"),
+        Ok(2)
+    );
+
+    let counter = handle(Counter::new());
+    let mut restored = tree(counter.clone());
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(counter.read(|node| node.count), 2);
+    assert_eq!(restored.pending(), original.pending());
+
+    assert_eq!(finish(&mut original), Status::Success);
+    assert_eq!(finish(&mut restored), Status::Success);
+    assert_eq!(restored.steps(), original.steps());
+    assert_eq!(restored.snapshot(), original.snapshot());
+}
+
+#[test]
+fn restore_requires_persistent_nodes() {
+    let tree = || {
+        Tree::new(sequence(vec![
+            action(|| Status::Success),
+            custom(handle(CallCounterAction::new(Status::Success))),
+        ]))
+    };
+    let snapshot = tree().snapshot();
+    assert_eq!(snapshot.nodes[2].kind, "CallCounterAction");
+    assert!(snapshot.nodes[2].state.is_none());
+    assert_eq!(
+        tree().restore(&snapshot),
+        Err(RestoreError::NotPersistent(NodeId(2)))
+    );
+}
+
+#[test]
+fn restore_rejects_a_different_tree() {
+    let snapshot = Tree::new(sequence(vec![wait(1)])).snapshot();
+    let mut other = Tree::new(selector(vec![wait(1)]));
+    assert!(matches!(
+        other.restore(&snapshot),
+        Err(RestoreError::KindMismatch {
+            node: NodeId(0),
+            ..
+        })
+    ));
+    let mut other = Tree::new(wait(1));
+    assert_eq!(
+        other.restore(&snapshot),
+        Err(RestoreError::NodeCount {
+            expected: 1,
+            found: 2
+        })
+    );
+}
+
+#[test]
+fn failed_restores_leave_the_tree_untouched() {
+    let tree = || Tree::new(sequence(vec![action(|| Status::Success), wait(2)]));
+    let mut original = tree();
+    original.start();
+    for _ in 0..3 {
+        original.step();
+    }
+    let mut snapshot = original.snapshot();
+    snapshot.nodes[2].state = Some(NodeState::new(Status::Running));
+
+    let mut restored = tree();
+    assert_eq!(
+        restored.restore(&snapshot),
+        Err(RestoreError::State {
+            node: NodeId(2),
+            error: ParamError::Missing("remaining".to_string()),
+        })
+    );
+    assert_eq!(restored.status(), Status::Invalid);
+    assert_eq!(restored.run(), Status::Success);
+
+    let mut snapshot = original.snapshot();
+    snapshot.nodes[0].state = Some(
+        NodeState::new(Status::Running).with("current_child", Param::Int(i64::from(i16::MAX) + 1)),
+    );
+    assert_eq!(
+        tree().restore(&snapshot),
+        Err(RestoreError::State {
+            node: NodeId(0),
+            error: ParamError::OutOfRange {
+                name: "current_child".to_string(),
+                value: i64::from(i16::MAX) + 1,
+            },
+        })
+    );
+}
+
+// Saves nothing but insists on a count when loading.
+#[derive(Behavior)]
+#[behave(persist)]
+struct Lossy {
+    #[behave(status)]
+    status: Status,
+}
+
+impl Update for Lossy {
+    fn update(&mut self, _: &mut Context) -> Status {
+        Status::Success
+    }
+}
+
+impl Persist for Lossy {
+    fn save(&self) -> NodeState {
+        NodeState::new(self.status)
+    }
+
+    fn load(&mut self, state: &NodeState) -> Result<(), ParamError> {
+        state.values.get_int("count")?;
+        self.status = state.status;
+        Ok(())
+    }
+}
+
+#[test]
+fn failed_rollbacks_are_reported() {
+    let tree = || {
+        Tree::new(sequence(vec![
+            custom(handle(Lossy {
+                status: Status::Invalid,
+            })),
+            wait(2),
+        ]))
+    };
+    let mut snapshot = tree().snapshot();
+    snapshot.nodes[1].state = Some(NodeState::new(Status::Running).with("count", Param::Int(1)));
+    snapshot.nodes[2].state = Some(NodeState::new(Status::Running));
+    assert_eq!(
+        tree().restore(&snapshot),
+        Err(RestoreError::Rollback {
+            node: NodeId(1),
+            error: ParamError::Missing("count".to_string()),
+        })
+    );
+}
+
+#[test]
+fn restore_brings_back_the_blackboard() {
+    let tree = || {
+        Tree::new(blackboard_condition(
+            "armed",
+            AbortMode::SelfOnly,
+            |value| value == Some(&Param::Bool(true)),
+            subtree(Scope::new(), wait(3)),
+        ))
+    };
+    let mut original = tree();
+    original.set_blackboard("armed", Param::Bool(true));
+    original.start();
+    for _ in 0..3 {
+        original.step();
+    }
+    let snapshot = original.snapshot();
+    assert_eq!(snapshot.blackboard.len(), 2);
+
+    let mut restored = tree();
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(restored.blackboard().get("armed"), Some(&Param::Bool(true)));
+    assert_eq!(finish(&mut original), Status::Success);
+    assert_eq!(finish(&mut restored), Status::Success);
+    assert_eq!(restored.snapshot(), original.snapshot());
+}
+
+#[test]
+fn restored_tree_keeps_waiting_for_events() {
+    let tree = || Tree::new(sequence(vec![wait_for("door"), action(|| Status::Success)]));
+    let mut original = tree();
+    original.start();
+    while original.step() {}
+    assert_eq!(original.waiting(), vec![NodeId(1)]);
+    let snapshot = original.snapshot();
+    assert_eq!(snapshot.waiting, vec![(NodeId(1), "door".to_string())]);
+
+    let mut restored = tree();
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(restored.waiting(), vec![NodeId(1)]);
+    assert_eq!(restored.notify("door"), 1);
+    assert_eq!(finish(&mut restored), Status::Success);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_round_trips_through_json() {
+    let mut tree = Tree::new(repeater(
+        sequence(vec![
+            wait(1),
+            action(|| Status::Success),
+            custom(handle(Counter::new())),
+        ]),
+        2,
+    ));
+    tree.start();
+    for _ in 0..4 {
+        tree.step();
+    }
+    let snapshot = tree.snapshot();
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let decoded: TreeSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, snapshot);
+}
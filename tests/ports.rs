@@ -0,0 +1,208 @@
+extern crate behave;
+
+use behave::helpers::*;
+use behave::ports::{PortError, Ports};
+use behave::registry::{NodeRegistry, NodeSpec, Param, ParamError};
+use behave::validate::{Diagnostic, Problem};
+use behave::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Behavior)]
+#[behave(ports)]
+struct Measure {
+    #[behave(status)]
+    status: Status,
+    error: Option<ParamError>,
+}
+
+impl Measure {
+    fn new() -> Self {
+        Self {
+            status: Status::Invalid,
+            error: None,
+        }
+    }
+}
+
+impl CustomBehavior for Measure {
+    fn ports() -> Ports {
+        Ports::new()
+            .input::<String>("target")
+            .input_or("scale", 1i64)
+            .output::<i64>("length")
+    }
+}
+
+impl Update for Measure {
+    fn update(&mut self, context: &mut Context) -> Status {
+        let measured = context.input::<String>("target").and_then(|target| {
+            let scale = context.input::<i64>("scale")?;
+            context.output("length", target.len() as i64 * scale)
+        });
+        match measured {
+            Ok(()) => Status::Success,
+            Err(error) => {
+                self.error = Some(error);
+                Status::Failure
+            }
+        }
+    }
+}
+
+#[test]
+fn ports_read_and_write_remapped_keys() {
+    let mut tree = Tree::new(sequence(vec![
+        NodeBuilder::custom(Measure::new())
+            .port("target", "{enemy}")
+            .port("length", "{enemy_length}"),
+        NodeBuilder::custom(Measure::new())
+            .port("target", "{friend}")
+            .port("length", "{friend_length}")
+            .port("scale", "10"),
+    ]));
+    tree.set_blackboard("enemy", Param::Str("orc".to_string()));
+    tree.set_blackboard("friend", Param::Str("elf".to_string()));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("enemy_length"), Some(&Param::Int(3)));
+    assert_eq!(
+        tree.blackboard().get("friend_length"),
+        Some(&Param::Int(30))
+    );
+}
+
+#[test]
+fn constants_are_typed_at_build_time() {
+    let mut tree = Tree::new(
+        NodeBuilder::custom(Measure::new())
+            .port("target", "goblin")
+            .port("length", "{length}"),
+    );
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("length"), Some(&Param::Int(6)));
+
+    let error = Tree::try_new(
+        NodeBuilder::custom(Measure::new())
+            .port("target", "goblin")
+            .port("length", "{length}")
+            .port("scale", "big"),
+    )
+    .err();
+    assert_eq!(
+        error,
+        Some(BuildError::Port(PortError::InvalidValue {
+            node: NodeId(0),
+            port: "scale".to_string(),
+            expected: "int",
+            value: "big".to_string(),
+        }))
+    );
+}
+
+#[test]
+fn bad_remaps_are_rejected() {
+    let unknown = Tree::try_new(sequence(vec![NodeBuilder::custom(Measure::new())
+        .port("target", "{enemy}")
+        .port("length", "{length}")
+        .port("speed", "1")]));
+    assert_eq!(
+        unknown.err(),
+        Some(BuildError::Port(PortError::UnknownPort {
+            node: NodeId(1),
+            port: "speed".to_string(),
+        }))
+    );
+
+    let output = Tree::try_new(
+        NodeBuilder::custom(Measure::new())
+            .port("target", "{enemy}")
+            .port("length", "length"),
+    );
+    assert_eq!(
+        output.err(),
+        Some(BuildError::Port(PortError::OutputNotKey {
+            node: NodeId(0),
+            port: "length".to_string(),
+        }))
+    );
+
+    let unbound = Tree::try_new(NodeBuilder::custom(Measure::new()).port("target", "{enemy}"));
+    assert_eq!(
+        unbound.err(),
+        Some(BuildError::UnboundPorts(vec![Diagnostic {
+            node: NodeId(0),
+            problem: Problem::UnboundPort("length".to_string()),
+        }]))
+    );
+}
+
+#[test]
+fn missing_keys_fail_in_update() {
+    let measure = Arc::new(Mutex::new(Measure::new()));
+    let mut tree = Tree::new(
+        custom(measure.clone())
+            .port("target", "{enemy}")
+            .port("length", "{length}"),
+    );
+    assert_eq!(tree.run(), Status::Failure);
+    assert_eq!(
+        measure.lock().unwrap().error,
+        Some(ParamError::Missing("enemy".to_string()))
+    );
+    assert_eq!(tree.ports(NodeId(0)).len(), 3);
+}
+
+#[test]
+fn specs_bind_ports_from_params() {
+    let mut registry = NodeRegistry::new();
+    registry.register("measure", |_| Ok(Measure::new()));
+    let spec = NodeSpec::parse("measure target={name} length={size} scale=2").unwrap();
+    let mut tree = Tree::new(registry.instantiate(&spec).unwrap());
+    tree.set_blackboard("name", Param::Str("troll".to_string()));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("size"), Some(&Param::Int(10)));
+}
+
+#[derive(Behavior)]
+#[behave(ports)]
+struct Mislabeled {
+    #[behave(status)]
+    status: Status,
+    error: Option<ParamError>,
+}
+
+impl CustomBehavior for Mislabeled {
+    fn ports() -> Ports {
+        Ports::new().output::<i64>("out")
+    }
+}
+
+impl Update for Mislabeled {
+    fn update(&mut self, context: &mut Context) -> Status {
+        match context.output("out", "not an int".to_string()) {
+            Ok(()) => Status::Success,
+            Err(error) => {
+                self.error = Some(error);
+                Status::Failure
+            }
+        }
+    }
+}
+
+#[test]
+fn outputs_must_match_the_declared_type() {
+    let node = Arc::new(Mutex::new(Mislabeled {
+        status: Status::Invalid,
+        error: None,
+    }));
+    let mut tree = Tree::new(custom(node.clone()).port("out", "{out}"));
+    assert_eq!(tree.run(), Status::Failure);
+    assert_eq!(
+        node.lock().unwrap().error,
+        Some(ParamError::WrongType {
+            name: "out".to_string(),
+            expected: "int",
+            found: "string",
+        })
+    );
+    assert_eq!(tree.blackboard().get("out"), None);
+}
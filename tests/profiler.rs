@@ -0,0 +1,57 @@
+extern crate behave;
+
+use behave::helpers::*;
+use behave::*;
+
+#[test]
+fn profiling_is_opt_in() {
+    let mut tree = Tree::new(action(|| Status::Success));
+    tree.run();
+    assert!(tree.profile_report().is_none());
+}
+
+#[test]
+fn counts_ticks_and_outcomes() {
+    let mut tree = Tree::new(repeater(
+        selector(vec![action(|| Status::Failure), named("pause", wait(2))]),
+        2,
+    ));
+    tree.enable_profiling();
+    assert_eq!(Status::Success, tree.run());
+
+    let report = tree.profile_report().unwrap();
+    assert_eq!(report.nodes.len(), 4);
+
+    let failing = report.node(NodeId(2)).unwrap();
+    assert_eq!(failing.ticks, 2);
+    assert_eq!(failing.failures, 2);
+    assert_eq!(failing.successes, 0);
+
+    let pause = report.node(NodeId(3)).unwrap();
+    assert_eq!(pause.name, "pause");
+    assert_eq!(pause.kind, "Wait");
+    assert_eq!(pause.ticks, 6);
+    assert_eq!(pause.successes, 2);
+    assert_eq!(pause.average_ticks_to_completion(), Some(3.0));
+
+    let root = report.node(NodeId(0)).unwrap();
+    assert_eq!(root.successes, 1);
+    assert_eq!(root.average_ticks_to_completion(), Some(1.0));
+    assert!(root.total_time >= root.max_time);
+    assert!(report.total_time() >= pause.total_time);
+}
+
+#[test]
+fn report_renders_a_row_per_node() {
+    let mut tree = Tree::new(sequence(vec![
+        action(|| Status::Success),
+        action(|| Status::Success),
+    ]));
+    tree.enable_profiling();
+    tree.run();
+    let report = tree.profile_report().unwrap();
+    let text = report.to_string();
+    assert_eq!(text.lines().count(), 4);
+    assert!(text.lines().nth(1).unwrap().starts_with("n0"));
+    assert_eq!(report.by_total_time().len(), 3);
+}
@@ -0,0 +1,175 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::registry::*;
+use behave::*;
+
+#[test]
+fn build_builtin_nodes_from_spec() {
+    let mut registry = NodeRegistry::new();
+    registry.register("counter", |params| {
+        Ok(CallCounterAction::new(params.get_status("result")?))
+    });
+    let spec = NodeSpec::new("sequence")
+        .child(NodeSpec::new("success"))
+        .child(
+            NodeSpec::new("repeater")
+                .param("count", Param::Int(2))
+                .child(NodeSpec::new("counter").param("result", Param::Str("success".into()))),
+        );
+    let mut tree = Tree::new(registry.instantiate(&spec).unwrap());
+    assert_eq!(Status::Success, tree.run());
+}
+
+#[test]
+fn custom_node_result_from_params() {
+    let mut registry = NodeRegistry::new();
+    registry.register("counter", |params| {
+        Ok(CallCounterAction::new(params.get_status("result")?))
+    });
+    let spec = NodeSpec::new("selector")
+        .child(NodeSpec::new("counter").param("result", Param::Str("failure".into())))
+        .child(NodeSpec::new("failure"));
+    let mut tree = Tree::new(registry.instantiate(&spec).unwrap());
+    assert_eq!(Status::Failure, tree.run());
+}
+
+#[test]
+fn unknown_node_is_reported() {
+    let spec = NodeSpec::new("sequence").child(NodeSpec::new("teleport"));
+    assert_eq!(
+        NodeRegistry::new().instantiate(&spec).err(),
+        Some(RegistryError::UnknownNode("teleport".into()))
+    );
+}
+
+#[test]
+fn missing_and_mistyped_parameters_are_reported() {
+    let mut registry = NodeRegistry::new();
+    registry.register("counter", |params| {
+        Ok(CallCounterAction::new(params.get_status("result")?))
+    });
+    let missing = NodeSpec::new("repeater").child(NodeSpec::new("success"));
+    assert_eq!(
+        registry.instantiate(&missing).err(),
+        Some(RegistryError::Parameter {
+            node: "repeater".into(),
+            error: ParamError::Missing("count".into()),
+        })
+    );
+
+    let mistyped = NodeSpec::new("counter").param("result", Param::Int(1));
+    assert_eq!(
+        registry.instantiate(&mistyped).err(),
+        Some(RegistryError::Parameter {
+            node: "counter".into(),
+            error: ParamError::WrongType {
+                name: "result".into(),
+                expected: "string",
+                found: "int",
+            },
+        })
+    );
+}
+
+#[test]
+fn out_of_range_parameters_are_reported() {
+    let negative = NodeSpec::new("wait").param("ticks", Param::Int(-1));
+    assert_eq!(
+        NodeRegistry::new().instantiate(&negative).err(),
+        Some(RegistryError::Parameter {
+            node: "wait".into(),
+            error: ParamError::OutOfRange {
+                name: "ticks".into(),
+                value: -1,
+            },
+        })
+    );
+
+    let huge = NodeSpec::new("repeater")
+        .param("count", Param::Int(i64::from(i32::MAX) + 1))
+        .child(NodeSpec::new("success"));
+    assert_eq!(
+        NodeRegistry::new().instantiate(&huge).err(),
+        Some(RegistryError::Parameter {
+            node: "repeater".into(),
+            error: ParamError::OutOfRange {
+                name: "count".into(),
+                value: i64::from(i32::MAX) + 1,
+            },
+        })
+    );
+}
+
+#[test]
+fn child_count_is_checked() {
+    let spec = NodeSpec::new("repeater").param("count", Param::Int(1));
+    assert_eq!(
+        NodeRegistry::new().instantiate(&spec).err(),
+        Some(RegistryError::ChildCount {
+            node: "repeater".into(),
+            expected: 1,
+            found: 0,
+        })
+    );
+}
+
+#[test]
+fn spec_names_are_applied() {
+    let spec = NodeSpec::new("sequence")
+        .named("root")
+        .child(NodeSpec::new("success").named("done"));
+    let tree = Tree::new(NodeRegistry::new().instantiate(&spec).unwrap());
+    let mut names = vec![];
+    tree.walk(|visit| names.push(visit.node.name().to_string()));
+    assert_eq!(names, vec!["root", "done"]);
+}
+
+#[test]
+fn parse_indented_spec() {
+    let mut registry = NodeRegistry::new();
+    registry.register("counter", |params| {
+        Ok(CallCounterAction::new(params.get_status("result")?))
+    });
+    let spec = NodeSpec::parse(
+        "# guard
+selector name=guard
+  sequence
+    wait ticks=2
+    counter result=failure
+  repeater count=2
+    success
+",
+    )
+    .unwrap();
+    assert_eq!(
+        spec,
+        NodeSpec::new("selector")
+            .named("guard")
+            .child(
+                NodeSpec::new("sequence")
+                    .child(NodeSpec::new("wait").param("ticks", Param::Int(2)))
+                    .child(NodeSpec::new("counter").param("result", Param::Str("failure".into())))
+            )
+            .child(
+                NodeSpec::new("repeater")
+                    .param("count", Param::Int(2))
+                    .child(NodeSpec::new("success"))
+            )
+    );
+    let mut tree = Tree::new(registry.instantiate(&spec).unwrap());
+    assert_eq!(Status::Success, tree.run());
+}
+
+#[test]
+fn parse_errors_report_line() {
+    assert_eq!(
+        NodeSpec::parse("sequence\n  success\nselector\n"),
+        Err(SpecParseError {
+            line: 3,
+            message: "only one root node".into(),
+        })
+    );
+    assert_eq!(NodeSpec::parse("repeater count\n").unwrap_err().line, 1);
+}
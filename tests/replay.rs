@@ -0,0 +1,149 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::helpers::*;
+use behave::replay::*;
+use behave::sync::{handle, Handle, SharedCell};
+use behave::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Agent {
+    first: Handle<CallCounterAction>,
+    second: Handle<CallCounterAction>,
+}
+
+impl Agent {
+    fn new(first: Status, second: Status) -> Self {
+        Self {
+            first: handle(CallCounterAction::new(first)),
+            second: handle(CallCounterAction::new(second)),
+        }
+    }
+
+    fn tree(&self) -> Tree {
+        Tree::new(selector(vec![
+            custom(self.first.clone()),
+            repeater(custom(self.second.clone()), 2),
+        ]))
+    }
+}
+
+fn record(agent: &Agent) -> (Status, ExecutionLog) {
+    let recorder = handle(ExecutionRecorder::new());
+    let mut tree = agent.tree();
+    tree.add_observer(recorder.clone());
+    let status = tree.run();
+    let log = recorder.read(|recorder| recorder.log.clone());
+    (status, log)
+}
+
+#[test]
+fn records_transitions_and_leaf_results() {
+    let (status, log) = record(&Agent::new(Status::Failure, Status::Success));
+    assert_eq!(Status::Success, status);
+    let results: Vec<_> = log.results.iter().map(|e| (e.node, e.status)).collect();
+    assert_eq!(
+        results,
+        vec![
+            (NodeId(1), Status::Failure),
+            (NodeId(3), Status::Success),
+            (NodeId(3), Status::Success),
+        ]
+    );
+    let root = log.transitions.iter().filter(|e| e.node == NodeId(0));
+    assert_eq!(
+        root.map(|e| e.status).collect::<Vec<_>>(),
+        vec![Status::Running, Status::Success]
+    );
+}
+
+#[test]
+fn replay_does_not_call_actions() {
+    let (recorded, log) = record(&Agent::new(Status::Failure, Status::Success));
+
+    let offline = Agent::new(Status::Success, Status::Failure);
+    let mut tree = offline.tree();
+    let replayed = handle(ExecutionRecorder::new());
+    tree.add_observer(replayed.clone());
+    tree.replay(log.clone());
+    assert_eq!(recorded, tree.run());
+    assert_eq!(tree.replay_divergence(), None);
+    assert_eq!(
+        replayed.read(|recorder| recorder.log.transitions.clone()),
+        log.transitions
+    );
+    assert_eq!(offline.first.read(|node| node.call_count), 0);
+    assert_eq!(offline.second.read(|node| node.call_count), 0);
+}
+
+#[test]
+fn replay_reports_divergence() {
+    let (_, log) = record(&Agent::new(Status::Success, Status::Success));
+    let mut tree = Tree::new(sequence(vec![
+        action(|| Status::Success),
+        action(|| Status::Success),
+    ]));
+    tree.replay(log);
+    assert_eq!(Status::Failure, tree.run());
+    assert_eq!(tree.replay_divergence(), Some((3, NodeId(2))));
+}
+
+#[test]
+fn replay_reproduces_recorded_transitions() {
+    let recorder = handle(ExecutionRecorder::new());
+    let mut original = Tree::new(sequence(vec![wait(2), action(|| Status::Success)]));
+    original.add_observer(recorder.clone());
+    assert_eq!(original.run(), Status::Success);
+    let log = recorder.read(|recorder| recorder.log.clone());
+
+    let replayed = handle(ExecutionRecorder::new());
+    let mut offline = Tree::new(sequence(vec![wait(2), action(|| Status::Success)]));
+    offline.add_observer(replayed.clone());
+    offline.replay(log.clone());
+    offline.start();
+    offline.step();
+    offline.step();
+    assert_eq!(offline.node(NodeId(1)).unwrap().status(), Status::Running);
+    while offline.step() {}
+    assert_eq!(offline.replay_divergence(), None);
+    assert_eq!(
+        replayed.read(|recorder| recorder.log.transitions.clone()),
+        log.transitions
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn log_round_trips_through_json() {
+    let (_, log) = record(&Agent::new(Status::Failure, Status::Success));
+    let json = serde_json::to_string(&log).unwrap();
+    assert_eq!(log, serde_json::from_str::<ExecutionLog>(&json).unwrap());
+}
+
+#[test]
+fn replay_does_not_initialize_leaves() {
+    let tree = |starts: Arc<AtomicUsize>| {
+        Tree::new(sequence(vec![
+            async_action(move || {
+                starts.fetch_add(1, Ordering::SeqCst);
+                async { Status::Success }
+            }),
+            action(|| Status::Success),
+        ]))
+    };
+    let recorder = handle(ExecutionRecorder::new());
+    let recorded = Arc::new(AtomicUsize::new(0));
+    let mut original = tree(recorded.clone());
+    original.add_observer(recorder.clone());
+    assert_eq!(original.run(), Status::Success);
+    assert_eq!(recorded.load(Ordering::SeqCst), 1);
+
+    let replayed = Arc::new(AtomicUsize::new(0));
+    let mut offline = tree(replayed.clone());
+    offline.replay(recorder.read(|recorder| recorder.log.clone()));
+    assert_eq!(offline.run(), Status::Success);
+    assert_eq!(offline.replay_divergence(), None);
+    assert_eq!(replayed.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,173 @@
+extern crate behave;
+
+use behave::helpers::*;
+use behave::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut polls = 0;
+    loop {
+        polls += 1;
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return (output, polls);
+        }
+        thread::park();
+    }
+}
+
+#[derive(Default)]
+struct State {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+#[derive(Clone, Default)]
+struct Signal(Arc<Mutex<State>>);
+
+impl Signal {
+    fn fire_after(&self, delay: Duration) {
+        let state = self.0.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            let mut state = state.lock().unwrap();
+            state.done = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+    }
+}
+
+impl Future for Signal {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<bool> {
+        let mut state = self.0.lock().unwrap();
+        if state.done {
+            Poll::Ready(true)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn parked_nodes_are_only_polled_when_woken() {
+    let signal = Signal::default();
+    let waiting = signal.clone();
+    let mut tree = Tree::new(sequence(vec![
+        async_action(move || waiting.clone()),
+        action(|| Status::Success),
+    ]));
+    tree.enable_profiling();
+    signal.fire_after(Duration::from_millis(20));
+
+    let (status, polls) = block_on(tree.run_async());
+    assert_eq!(status, Status::Success);
+    assert!(polls <= 4, "polled {} times", polls);
+    let report = tree.profile_report().unwrap();
+    assert_eq!(report.node(NodeId(1)).unwrap().ticks, 2);
+}
+
+#[test]
+fn polled_nodes_still_advance_each_tick() {
+    let signal = Signal::default();
+    let waiting = signal.clone();
+    let mut tree = Tree::new(selector(vec![
+        sequence(vec![wait(3), action(|| Status::Failure)]),
+        async_action(move || waiting.clone()),
+    ]));
+    signal.fire_after(Duration::from_millis(5));
+    let (status, _) = block_on(tree.run_async());
+    assert_eq!(status, Status::Success);
+}
+
+#[test]
+fn synchronous_runs_work_after_an_async_run() {
+    let signal = Signal::default();
+    let waiting = signal.clone();
+    let mut tree = Tree::new(async_action(move || waiting.clone()));
+    signal.fire_after(Duration::from_millis(1));
+    assert_eq!(block_on(tree.run_async()).0, Status::Success);
+    assert_eq!(tree.run(), Status::Success);
+}
+
+#[test]
+fn dropping_the_future_keeps_parked_nodes_running() {
+    let signal = Signal::default();
+    let waiting = signal.clone();
+    let mut tree = Tree::new(async_action(move || waiting.clone()));
+    {
+        let mut future = Box::pin(tree.run_async());
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let poll = future.as_mut().poll(&mut Context::from_waker(&waker));
+        assert_eq!(poll, Poll::Pending);
+    }
+    assert_eq!(tree.pending(), vec![NodeId(0)]);
+    tree.tick();
+    assert_eq!(tree.steps(), 2);
+    signal.fire_after(Duration::from_millis(0));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(tree.tick(), Status::Success);
+}
+
+#[test]
+fn async_runs_wait_for_events_from_a_notifier() {
+    let mut tree = Tree::new(sequence(vec![wait_for("go"), action(|| Status::Success)]));
+    let notifier = tree.notifier();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(5));
+        notifier.notify("go");
+    });
+    assert_eq!(block_on(tree.run_async()).0, Status::Success);
+}
+
+// A waker that re-enters the tree's queue while it's being woken.
+struct Renotify {
+    notifier: driver::Notifier,
+    woken: AtomicUsize,
+}
+
+impl Wake for Renotify {
+    fn wake(self: Arc<Self>) {
+        if self.woken.fetch_add(1, Ordering::SeqCst) == 0 {
+            self.notifier.notify("again");
+        }
+    }
+}
+
+#[test]
+fn wakers_can_reenter_the_queue() {
+    let mut tree = Tree::new(wait_for("go"));
+    let notifier = tree.notifier();
+    let renotify = Arc::new(Renotify {
+        notifier: notifier.clone(),
+        woken: AtomicUsize::new(0),
+    });
+    let waker = Waker::from(renotify.clone());
+    let mut future = Box::pin(tree.run_async());
+    let poll = future.as_mut().poll(&mut Context::from_waker(&waker));
+    assert_eq!(poll, Poll::Pending);
+    notifier.notify("go");
+    assert_eq!(renotify.woken.load(Ordering::SeqCst), 2);
+    let poll = future.as_mut().poll(&mut Context::from_waker(&waker));
+    assert_eq!(poll, Poll::Ready(Status::Success));
+}
@@ -0,0 +1,213 @@
+extern crate behave;
+
+use behave::blackboard::{AbortMode, Scope};
+use behave::helpers::*;
+use behave::ports::Ports;
+use behave::registry::{NodeRegistry, NodeSpec, Param, ParamError, RegistryError};
+use behave::*;
+
+#[derive(Behavior)]
+struct Count {
+    #[behave(status)]
+    status: Status,
+}
+
+impl Update for Count {
+    fn update(&mut self, context: &mut Context) -> Status {
+        let mut blackboard = context.blackboard_mut();
+        let count = match blackboard.get("count") {
+            Some(Param::Int(count)) => *count,
+            _ => 0,
+        };
+        blackboard.set("count", Param::Int(count + 1));
+        Status::Success
+    }
+}
+
+fn count() -> NodeBuilder {
+    NodeBuilder::custom(Count {
+        status: Status::Invalid,
+    })
+}
+
+#[derive(Behavior)]
+#[behave(ports)]
+struct CopyValue {
+    #[behave(status)]
+    status: Status,
+}
+
+impl CustomBehavior for CopyValue {
+    fn ports() -> Ports {
+        Ports::new().input::<String>("from").output::<String>("to")
+    }
+}
+
+impl Update for CopyValue {
+    fn update(&mut self, context: &mut Context) -> Status {
+        let copied = context
+            .input::<String>("from")
+            .and_then(|value| context.output("to", value));
+        match copied {
+            Ok(()) => Status::Success,
+            Err(_) => Status::Failure,
+        }
+    }
+}
+
+#[test]
+fn subtrees_keep_their_keys_to_themselves() {
+    let mut tree = Tree::new(sequence(vec![
+        subtree(Scope::new(), count()),
+        subtree(Scope::new(), count()),
+        count(),
+    ]));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("count"), Some(&Param::Int(1)));
+    for id in [NodeId(1), NodeId(3)] {
+        let scope = tree.scope(id).unwrap();
+        assert_eq!(
+            tree.blackboard().get_in(scope, "count"),
+            Some(&Param::Int(1))
+        );
+    }
+    assert_eq!(tree.blackboard().scope_count(), 3);
+}
+
+#[test]
+fn remapped_and_inherited_keys_reach_the_parent() {
+    let mut tree = Tree::new(sequence(vec![
+        subtree(Scope::new().remap("count", "{hits}"), count()),
+        subtree(Scope::new().inherit(), count()),
+        subtree(Scope::new().inherit(), count()),
+    ]));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("hits"), Some(&Param::Int(1)));
+    assert_eq!(tree.blackboard().get("count"), Some(&Param::Int(2)));
+}
+
+#[test]
+fn ports_resolve_through_the_scope() {
+    let copy = NodeBuilder::custom(CopyValue {
+        status: Status::Invalid,
+    })
+    .port("from", "{target}")
+    .port("to", "{seen}");
+    let mut tree = Tree::new(subtree(Scope::new().remap("target", "enemy"), copy));
+    tree.set_blackboard("enemy", Param::Str("orc".to_string()));
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("seen"), None);
+    let scope = tree.scope(NodeId(1)).unwrap();
+    assert_eq!(
+        tree.blackboard().get_in(scope, "seen"),
+        Some(&Param::Str("orc".to_string()))
+    );
+}
+
+#[test]
+fn observers_watch_remapped_keys() {
+    let guarded = blackboard_condition(
+        "alarm",
+        AbortMode::SelfOnly,
+        |value| value == Some(&Param::Bool(true)),
+        wait(100),
+    );
+    let mut tree = Tree::new(selector(vec![
+        subtree(Scope::new().remap("alarm", "enemy"), guarded),
+        action(|| Status::Success),
+    ]));
+    tree.set_blackboard("enemy", Param::Bool(true));
+    tree.start();
+    tree.step();
+    tree.step();
+    tree.set_blackboard("alarm", Param::Bool(false));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Running);
+    tree.set_blackboard("enemy", Param::Bool(false));
+    assert_eq!(tree.node(NodeId(3)).unwrap().status(), Status::Aborted);
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+}
+
+#[test]
+fn registry_instantiates_subtree_templates() {
+    let mut registry = NodeRegistry::new();
+    registry.register("count", |_| {
+        Ok(Count {
+            status: Status::Invalid,
+        })
+    });
+    registry.register_subtree("tally", NodeSpec::parse("count").unwrap());
+    let spec = NodeSpec::parse(
+        "
+sequence
+  subtree ref=tally count={left}
+  subtree ref=tally count={right}
+  subtree ref=tally count={right}
+  subtree ref=tally name=local
+",
+    )
+    .unwrap();
+    let mut tree = Tree::new(registry.instantiate(&spec).unwrap());
+    assert_eq!(tree.run(), Status::Success);
+    assert_eq!(tree.blackboard().get("left"), Some(&Param::Int(1)));
+    assert_eq!(tree.blackboard().get("right"), Some(&Param::Int(2)));
+    assert_eq!(tree.blackboard().get("count"), None);
+    assert_eq!(tree.node(NodeId(1)).unwrap().name(), "tally");
+    assert_eq!(tree.node(NodeId(7)).unwrap().name(), "local");
+}
+
+#[test]
+fn registry_rejects_bad_subtree_refs() {
+    let mut registry = NodeRegistry::new();
+    let missing = NodeSpec::parse("subtree ref=patrol").unwrap();
+    assert_eq!(
+        registry.instantiate(&missing).err(),
+        Some(RegistryError::UnknownSubtree("patrol".to_string()))
+    );
+
+    registry.register_subtree(
+        "patrol",
+        NodeSpec::parse("sequence\n  subtree ref=patrol").unwrap(),
+    );
+    assert_eq!(
+        registry.instantiate(&missing).err(),
+        Some(RegistryError::RecursiveSubtree("patrol".to_string()))
+    );
+}
+
+#[test]
+fn registry_rejects_subtree_params_that_are_not_keys() {
+    let mut registry = NodeRegistry::new();
+    registry.register_subtree("patrol", NodeSpec::parse("success").unwrap());
+    let error = |source: &str| match registry.instantiate(&NodeSpec::parse(source).unwrap()) {
+        Err(RegistryError::Parameter { error, .. }) => Some(error),
+        _ => None,
+    };
+    assert_eq!(
+        error("subtree ref=patrol count=5"),
+        Some(ParamError::WrongType {
+            name: "count".to_string(),
+            expected: "a `{key}`",
+            found: "int",
+        })
+    );
+    assert_eq!(
+        error("subtree ref=patrol target=enemy"),
+        Some(ParamError::WrongType {
+            name: "target".to_string(),
+            expected: "a `{key}`",
+            found: "string",
+        })
+    );
+    assert_eq!(
+        error("subtree ref=patrol inherit=yes"),
+        Some(ParamError::WrongType {
+            name: "inherit".to_string(),
+            expected: "bool",
+            found: "string",
+        })
+    );
+    assert!(registry
+        .instantiate(&NodeSpec::parse("subtree ref=patrol inherit=false").unwrap())
+        .is_ok());
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "send")]
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::helpers::*;
+use behave::observer::Recorder;
+use behave::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn trees_are_send() {
+    assert_send::<Tree>();
+    assert_send::<NodeBuilder>();
+}
+
+#[test]
+fn trees_run_on_worker_threads() {
+    let counter = Arc::new(Mutex::new(CallCounterAction::new(Status::Success)));
+    let recorder = Arc::new(Mutex::new(Recorder::new()));
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let mut tree = Tree::new(sequence(vec![
+                wait(2),
+                custom(Arc::new(Mutex::new(CallCounterAction::new(
+                    Status::Success,
+                )))),
+                custom(counter.clone()),
+            ]));
+            tree.add_observer(recorder.clone());
+            thread::spawn(move || tree.run())
+        })
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), Status::Success);
+    }
+    assert_eq!(counter.lock().unwrap().call_count, 4);
+    assert!(!recorder.lock().unwrap().records.is_empty());
+}
@@ -0,0 +1,116 @@
+extern crate behave;
+mod action;
+
+use action::CallCounterAction;
+use behave::blackboard::{AbortMode, Observe};
+use behave::helpers::*;
+use behave::registry::Param;
+use behave::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn shared_instances_are_rejected() {
+    let node = Arc::new(Mutex::new(CallCounterAction::new(Status::Success)));
+    let built = Tree::try_new(sequence(vec![
+        custom(node.clone()),
+        wait(1),
+        custom(node.clone()),
+    ]));
+    assert_eq!(
+        built.err(),
+        Some(BuildError::SharedNode {
+            first: NodeId(1),
+            second: NodeId(3),
+        })
+    );
+    assert_eq!(Arc::strong_count(&node), 1);
+}
+
+#[test]
+#[should_panic(expected = "same shared instance")]
+fn new_panics_on_shared_instances() {
+    let node = Arc::new(Mutex::new(CallCounterAction::new(Status::Success)));
+    Tree::new(selector(vec![custom(node.clone()), custom(node)]));
+}
+
+#[test]
+fn separate_trees_can_share_an_instance() {
+    let node = Arc::new(Mutex::new(CallCounterAction::new(Status::Success)));
+    let mut first = Tree::new(custom(node.clone()));
+    let mut second = Tree::new(sequence(vec![custom(node.clone())]));
+    assert_eq!(first.run(), Status::Success);
+    assert_eq!(second.run(), Status::Success);
+    assert_eq!(node.lock().unwrap().call_count, 2);
+}
+
+// Runs its child while the "alert" flag it observes is set.
+struct Sentry {
+    status: Status,
+}
+
+impl Behavior for Sentry {
+    fn initialize(&mut self, context: &mut Context) {
+        context.start_child(0);
+        self.status = Status::Running;
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    fn child_complete(&mut self, _: usize, result: Status, _: &mut Context) -> Status {
+        result
+    }
+
+    fn on_complete(&mut self, result: Status) {
+        self.status = result;
+    }
+
+    fn abort(&mut self) -> Status {
+        self.status = Status::Aborted;
+        self.status
+    }
+
+    fn observe(&self) -> Option<&dyn Observe> {
+        Some(self)
+    }
+}
+
+impl Observe for Sentry {
+    fn key(&self) -> &str {
+        "alert"
+    }
+
+    fn abort_mode(&self) -> AbortMode {
+        AbortMode::SelfOnly
+    }
+
+    fn check(&self, value: Option<&Param>) -> bool {
+        value == Some(&Param::Bool(true))
+    }
+}
+
+impl CustomBehavior for Sentry {}
+
+#[test]
+fn shared_nodes_keep_their_observer() {
+    let node = Arc::new(Mutex::new(Sentry {
+        status: Status::Invalid,
+    }));
+    let mut tree = Tree::new(selector(vec![
+        custom(node.clone()).with_children(vec![wait(100)]),
+        action(|| Status::Success),
+    ]));
+    tree.set_blackboard("alert", Param::Bool(true));
+    tree.start();
+    for _ in 0..3 {
+        tree.step();
+    }
+    assert_eq!(tree.node(NodeId(2)).unwrap().status(), Status::Running);
+
+    tree.set_blackboard("alert", Param::Bool(false));
+    assert_eq!(tree.node(NodeId(2)).unwrap().status(), Status::Aborted);
+    assert_eq!(node.lock().unwrap().status, Status::Failure);
+    while tree.step() {}
+    assert_eq!(tree.status(), Status::Success);
+}
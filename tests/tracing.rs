@@ -0,0 +1,86 @@
+#![cfg(feature = "tracing")]
+extern crate behave;
+
+use behave::helpers::*;
+use behave::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[derive(Default)]
+struct Collector {
+    next_span: AtomicU64,
+    spans: Mutex<Vec<String>>,
+    messages: Mutex<Vec<String>>,
+}
+
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+struct Handle(Arc<Collector>);
+
+impl Subscriber for Handle {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes) -> Id {
+        self.0
+            .spans
+            .lock()
+            .unwrap()
+            .push(span.metadata().name().to_string());
+        Id::from_u64(self.0.next_span.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event) {
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        self.0.messages.lock().unwrap().push(message.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn emits_spans_and_transitions() {
+    let collector = Arc::new(Collector::default());
+    let mut tree = Tree::new(sequence(vec![
+        action(|| Status::Success),
+        repeater(action(|| Status::Success), 2),
+    ]));
+    tracing::subscriber::with_default(Handle(collector.clone()), || {
+        assert_eq!(Status::Success, tree.run());
+    });
+
+    assert!(collector
+        .spans
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|span| span == "node"));
+    assert!(!collector.spans.lock().unwrap().is_empty());
+    let messages = collector.messages.lock().unwrap();
+    for expected in &["child complete", "loop complete", "status changed"] {
+        assert!(
+            messages.iter().any(|message| message == expected),
+            "missing {}",
+            expected
+        );
+    }
+}
@@ -0,0 +1,113 @@
+extern crate behave;
+
+use behave::blackboard::Scope;
+use behave::helpers::*;
+use behave::nodes::{Repeater, Sequence, Subtree};
+use behave::ports::Ports;
+use behave::validate::{Diagnostic, Problem};
+use behave::*;
+
+#[derive(Behavior)]
+#[behave(ports)]
+struct Aim {
+    #[behave(status)]
+    status: Status,
+}
+
+impl CustomBehavior for Aim {
+    fn ports() -> Ports {
+        Ports::new()
+            .input::<String>("target")
+            .input_or("spread", 0.5)
+    }
+}
+
+impl Update for Aim {
+    fn update(&mut self, _: &mut Context) -> Status {
+        Status::Success
+    }
+}
+
+fn diagnostic(node: usize, problem: Problem) -> Diagnostic {
+    Diagnostic {
+        node: NodeId(node),
+        problem,
+    }
+}
+
+#[test]
+fn well_formed_trees_have_no_diagnostics() {
+    let tree = Tree::new(sequence(vec![
+        named("first", repeater(wait(1), 2)),
+        subtree(Scope::new(), action(|| Status::Success)),
+        NodeBuilder::custom(Aim {
+            status: Status::Invalid,
+        })
+        .port("target", "{enemy}"),
+    ]));
+    assert!(tree.validate().is_empty());
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "composite has no children"))]
+fn debug_builds_reject_invalid_trees() {
+    Tree::new(sequence(vec![selector(vec![])]));
+}
+
+#[test]
+fn diagnostics_cover_each_problem() {
+    let built = Tree::try_new(sequence(vec![
+        NodeBuilder::new(Sequence::new()),
+        NodeBuilder::new(Repeater::new(0)).with_children(vec![named("twice", wait(0))]),
+        NodeBuilder::new(Subtree::new()).named("patrol"),
+        named("twice", wait(0)),
+    ]));
+    let tree = match built {
+        Ok(tree) => tree,
+        Err(error) => panic!("{}", error),
+    };
+    let diagnostics = tree.validate();
+    assert_eq!(
+        diagnostics,
+        vec![
+            diagnostic(1, Problem::EmptyComposite),
+            diagnostic(2, Problem::NoRepeats(0)),
+            diagnostic(4, Problem::UnresolvedSubtree("patrol".to_string())),
+            diagnostic(5, Problem::DuplicateName("twice".to_string())),
+        ]
+    );
+    assert!(!diagnostics[3].is_error());
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "node n1: composite has no children"
+    );
+}
+
+#[test]
+fn every_unbound_port_is_reported() {
+    let aim = || {
+        NodeBuilder::custom(Aim {
+            status: Status::Invalid,
+        })
+    };
+    let built = Tree::try_new(sequence(vec![
+        aim(),
+        aim().port("target", "{enemy}"),
+        aim(),
+    ]));
+    let error = match built {
+        Ok(_) => panic!("unbound ports were accepted"),
+        Err(error) => error,
+    };
+    assert_eq!(
+        error,
+        BuildError::UnboundPorts(vec![
+            diagnostic(1, Problem::UnboundPort("target".to_string())),
+            diagnostic(3, Problem::UnboundPort("target".to_string())),
+        ])
+    );
+    assert_eq!(
+        error.to_string(),
+        "node n1: port `target` is not bound\nnode n3: port `target` is not bound"
+    );
+}